@@ -1,5 +1,5 @@
-use harperdb_sdk_rust::{ HarperConfig, Harper };
-use harperdb_sdk_rust as harper;
+use harperdb::{ HarperConfig, Harper };
+use harperdb as harper;
 use serde_json::{Value};
 use std::{error::Error};
 
@@ -9,10 +9,11 @@ extern crate serde_json;
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
     let config: HarperConfig = HarperConfig {
-        url: "http://0.0.0.0:9925/",
-        username: "HDB_ADMIN",
-        password: "password",
-        schema: "shop",
+        url: "http://0.0.0.0:9925/".to_string(),
+        username: "HDB_ADMIN".to_string(),
+        password: "password".to_string(),
+        schema: "shop".to_string(),
+        ..Default::default()
     };
 
     let harper_client = Harper::new(config);
@@ -20,8 +21,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     // Describe Table ------------------------------------------
     let table_option: harper::TableOptions = harper::TableOptions {
-        table: "test_table",
-        schema: "describe_schema_test",
+        table: "test_table".to_string(),
+        schema: "describe_schema_test".to_string(),
     };
     let result = harper_client.describe_table(table_option).await?;
 
@@ -32,7 +33,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     // Create Schema ------------------------------------------
     let schema_option: harper::SchemaOption = harper::SchemaOption {
-        schema: "newschema2",
+        schema: "newschema2".to_string(),
     };
     let result = harper_client.create_schema(schema_option).await?;
 
@@ -43,7 +44,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     // Delete Schema ------------------------------------------
     let schema_option: harper::SchemaOption = harper::SchemaOption {
-        schema: "newschema22",
+        schema: "newschema22".to_string(),
     };
     let result = harper_client.drop_schema(schema_option).await?;
 
@@ -54,7 +55,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     //Describe Schema ------------------------------------------
     let schema_option: harper::SchemaOption = harper::SchemaOption {
-        schema: "describe_schema_test",
+        schema: "describe_schema_test".to_string(),
     };
     let result = harper_client.describe_schema(schema_option).await?;
 
@@ -73,9 +74,9 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     // Create Table ------------------------------------------
     let create_table_option: harper::CreateTableOptions = harper::CreateTableOptions {
-        hash_attribute: "id",
-        table: "test_table",
-        schema: "describe_schema_test",
+        hash_attribute: "id".to_string(),
+        table: "test_table".to_string(),
+        schema: "describe_schema_test".to_string(),
     };
     let result = harper_client.create_table(create_table_option).await?;
 
@@ -86,8 +87,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     // Drop Table  ------------------------------------------
     let table_option: harper::TableOptions = harper::TableOptions {
-        table: "test2",
-        schema: "newschema",
+        table: "test2".to_string(),
+        schema: "newschema".to_string(),
     };
     let result = harper_client.drop_table(table_option).await?;
 
@@ -111,8 +112,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     // Insert Records  ------------------------------------------
     let insert_option: harper::QueryOptions = harper::QueryOptions {
-        table: "crud_table_test",
-        schema: "testing",
+        table: "crud_table_test".to_string(),
+        schema: "testing".to_string(),
         records:json!([{
             "id": "record1234",
             "name": "Incredible Metal Chair",
@@ -133,8 +134,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     // Update Records ------------------------------------------
     let update_option: harper::QueryOptions = harper::QueryOptions {
-        table: "crud_table_test",
-        schema: "testing",
+        table: "crud_table_test".to_string(),
+        schema: "testing".to_string(),
         records:json!([{            
             "id": "record1234",           
             "color": "red",            
@@ -226,26 +227,20 @@ async fn main() -> Result<(), Box<dyn Error>> {
     println!("{:#?}", v);
 
     // List Users ------------------------------------------    
-    let result = harper_client.list_users().await?;
+    let users = harper_client.list_users().await?;
 
-    println!("{:#?}", result.status());
-    let v: Value =  result.json().await?;    
-    //let data = result.text().await?;
-    println!("{:#?}", v);
+    println!("{:#?}", users);
 
     // User Info ------------------------------------------    
-    let result = harper_client.user_info().await?;
+    let user = harper_client.user_info().await?;
 
-    println!("{:#?}", result.status());
-    let v: Value =  result.json().await?;    
-    //let data = result.text().await?;
-    println!("{:#?}", v);
+    println!("{:#?}", user);
     
     // Add User  ------------------------------------------    
     let user_option: harper::UserAddOptions = harper::UserAddOptions {
-        role: "c0a90733-1fc3-48df-a16b-d7c3011b63b2",
-        username: "john",
-        password: "secret",
+        role: "c0a90733-1fc3-48df-a16b-d7c3011b63b2".to_string(),
+        username: "john".to_string(),
+        password: "secret".to_string(),
         active: true
     };
     let result = harper_client.add_user(user_option).await?;
@@ -257,10 +252,10 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     // Alter User  ------------------------------------------    
      let user_option: harper::UserAlterOptions = harper::UserAlterOptions {
-        role: Some("c0a90733-1fc3-48df-a16b-d7c3011b63b2"),
-        username: "john",
-        password: Some("secret2"),
-        active: true
+        role: Some("c0a90733-1fc3-48df-a16b-d7c3011b63b2".to_string()),
+        username: "john".to_string(),
+        password: Some("secret2".to_string()),
+        active: Some(true)
     };
     let result = harper_client.alter_user(user_option).await?;
 
@@ -271,7 +266,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     // Drop User  ------------------------------------------    
     let user_option: harper::UserDropOptions = harper::UserDropOptions {
-        username: "john",
+        username: "john".to_string(),
     };
     let result = harper_client.drop_user(user_option).await?;
 
@@ -386,22 +381,19 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let read_logs_option: harper::LogsOptions = harper::LogsOptions {
         limit : Some(2),
         start : Some(0),
+        level : None,
         from : None,
         until : None,
         order : None,
-        // attributes :None
     };
-    let result = harper_client.read_logs(read_logs_option).await?;
+    let entries = harper_client.read_logs(read_logs_option).await?;
 
-    println!("{:#?}", result.status());
-    let v: Value =  result.json().await?;    
-    //let data = result.text().await?;
-    println!("{:#?}", v);
+    println!("{:#?}", entries);
 
     // Search Jobs By Start Date ------------------------------------------    
     let search_jobs_by_start_date_option: harper::JobsByDateOptions = harper::JobsByDateOptions {
-        from_date : "2019-01-01",
-        to_date : "2020-12-30",
+        from_date : "2019-01-01".parse()?,
+        to_date : "2020-12-30".parse()?,
     };
 
     let result = harper_client.search_jobs_by_start_date(search_jobs_by_start_date_option).await?;
@@ -413,7 +405,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     // Get Job ------------------------------------------    
     let get_job_option: harper::GetJobOptions = harper::GetJobOptions {
-        id : "d8b70ed4-a62a-45ef-bf86-15508c4ba10a",
+        id : "d8b70ed4-a62a-45ef-bf86-15508c4ba10a".to_string(),
     };
 
     let result = harper_client.get_job(get_job_option).await?;
@@ -0,0 +1,300 @@
+use crate::error::Error;
+use crate::{Harper, QueryOptions, RowDeleteOptions};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+
+enum Step {
+    Insert(QueryOptions),
+    Update(QueryOptions),
+    Delete(RowDeleteOptions),
+}
+
+impl Step {
+    fn schema_table(&self) -> (&str, &str) {
+        match self {
+            Step::Insert(options) | Step::Update(options) => (&options.schema, &options.table),
+            Step::Delete(options) => (options.schema, options.table),
+        }
+    }
+}
+
+/// A sequence of insert, update and delete operations that is undone as a whole
+/// when one of them fails
+///
+/// HarperDB has no multi-operation transactions. A batch runs its steps in order
+/// and records how to undo each one: inserted records are deleted again, and the
+/// records an update or delete touches are read with `search_by_hash` first so
+/// they can be written back. When a step fails, the completed steps are undone
+/// in reverse order.
+///
+/// Other clients are not isolated from a running batch: they can see its
+/// intermediate state, and a rollback overwrites their changes to the records the
+/// batch touched.
+pub struct Batch<'a> {
+    harper: &'a Harper,
+    steps: Vec<Step>,
+}
+
+/// The responses of the steps of a successful batch, in order
+#[derive(Debug)]
+pub struct BatchReport {
+    pub responses: Vec<Value>,
+}
+
+/// One compensating operation sent while rolling a batch back
+#[derive(Debug)]
+pub struct Compensation {
+    /// Index of the step it undoes
+    pub step: usize,
+    /// The operation sent, e.g. a `delete` of the hashes a step inserted
+    pub operation: Value,
+    /// Set when the compensation itself failed, leaving the records it names in
+    /// the state the step left them in
+    pub error: Option<Error>,
+}
+
+/// A failed batch and what was done to undo it
+#[derive(Debug)]
+pub struct BatchError {
+    /// Index of the step that failed
+    pub step: usize,
+    pub error: Error,
+    /// Compensations in the order they were applied, last completed step first
+    pub rolled_back: Vec<Compensation>,
+}
+
+impl BatchError {
+    /// True when every compensating operation succeeded
+    pub fn is_rolled_back(&self) -> bool {
+        self.rolled_back.iter().all(|compensation| compensation.error.is_none())
+    }
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let failed = self.rolled_back.iter().filter(|compensation| compensation.error.is_some()).count();
+        write!(f, "batch step {} failed: {}; ", self.step, self.error)?;
+        if failed == 0 {
+            write!(f, "rolled back {} completed steps", self.rolled_back.len())
+        } else {
+            write!(f, "{} of {} compensating operations failed", failed, self.rolled_back.len())
+        }
+    }
+}
+
+impl std::error::Error for BatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl<'a> Batch<'a> {
+    pub(crate) fn new(harper: &'a Harper) -> Self {
+        Batch {
+            harper,
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn insert(mut self, options: QueryOptions) -> Self {
+        self.steps.push(Step::Insert(options));
+        self
+    }
+
+    pub fn update(mut self, options: QueryOptions) -> Self {
+        self.steps.push(Step::Update(options));
+        self
+    }
+
+    pub fn delete(mut self, options: RowDeleteOptions) -> Self {
+        self.steps.push(Step::Delete(options));
+        self
+    }
+
+    /// Run the steps in order, rolling the completed ones back when one fails
+    pub async fn run(self) -> Result<BatchReport, BatchError> {
+        let mut hash_attributes = HashMap::new();
+        let mut responses = Vec::new();
+        let mut undo = Vec::new();
+
+        for (index, step) in self.steps.iter().enumerate() {
+            match self.apply(step, &mut hash_attributes).await {
+                Ok((response, compensation)) => {
+                    responses.push(response);
+                    undo.push((index, compensation));
+                }
+                Err(error) => {
+                    let mut rolled_back = Vec::new();
+                    for (step, operation) in undo.into_iter().rev() {
+                        if let Some(operation) = operation {
+                            let error = self.send(&operation).await.err();
+                            rolled_back.push(Compensation { step, operation, error });
+                        }
+                    }
+                    return Err(BatchError {
+                        step: index,
+                        error,
+                        rolled_back,
+                    });
+                }
+            }
+        }
+
+        Ok(BatchReport { responses })
+    }
+
+    /// Run one step, returning its response and the operation that undoes it
+    /// (none when the step changed nothing).
+    async fn apply(
+        &self,
+        step: &Step,
+        hash_attributes: &mut HashMap<(String, String), String>,
+    ) -> Result<(Value, Option<Value>), Error> {
+        let (schema, table) = step.schema_table();
+        let key = (schema.to_string(), table.to_string());
+        let hash_attribute = match hash_attributes.get(&key) {
+            Some(hash_attribute) => hash_attribute.clone(),
+            None => {
+                let description = self
+                    .send(&json!({ "operation": "describe_table", "schema": schema, "table": table }))
+                    .await?;
+                let hash_attribute = description["hash_attribute"].as_str().unwrap_or("id").to_string();
+                hash_attributes.insert(key, hash_attribute.clone());
+                hash_attribute
+            }
+        };
+        let hash_attribute = hash_attribute.as_str();
+
+        match step {
+            Step::Insert(options) => {
+                let response = self
+                    .send(&json!({ "operation": "insert", "schema": schema, "table": table, "records": options.records }))
+                    .await?;
+                let submitted = hashes(&options.records, hash_attribute);
+                let inserted: Vec<Value> = touched(&response, "inserted_hashes", || submitted.clone())
+                    .into_iter()
+                    .map(|hash| submitted.iter().find(|value| same_hash(value, &hash)).cloned().unwrap_or(hash))
+                    .collect();
+                let compensation = if inserted.is_empty() {
+                    None
+                } else {
+                    Some(json!({ "operation": "delete", "schema": schema, "table": table, "hash_values": inserted }))
+                };
+                Ok((response, compensation))
+            }
+            Step::Update(options) => {
+                let prior = self.capture(schema, table, hashes(&options.records, hash_attribute)).await?;
+                let response = self
+                    .send(&json!({ "operation": "update", "schema": schema, "table": table, "records": options.records }))
+                    .await?;
+                let updated = touched(&response, "update_hashes", || hashes(&options.records, hash_attribute));
+                let updates = options.records.as_array().map(Vec::as_slice).unwrap_or_default();
+
+                // Write the prior values back, and clear attributes the update added.
+                let restore: Vec<Value> = prior
+                    .into_iter()
+                    .filter(|record| updated.iter().any(|hash| same_hash(hash, &record[hash_attribute])))
+                    .map(|mut record| {
+                        let update = updates.iter().find(|update| same_hash(&update[hash_attribute], &record[hash_attribute]));
+                        if let (Some(Value::Object(update)), Value::Object(fields)) = (update, &mut record) {
+                            for attribute in update.keys() {
+                                fields.entry(attribute.clone()).or_insert(Value::Null);
+                            }
+                        }
+                        without_timestamps(record)
+                    })
+                    .collect();
+                let compensation = if restore.is_empty() {
+                    None
+                } else {
+                    Some(json!({ "operation": "update", "schema": schema, "table": table, "records": restore }))
+                };
+                Ok((response, compensation))
+            }
+            Step::Delete(options) => {
+                let hash_values: Vec<Value> = options.hash_values.iter().map(|hash| json!(hash)).collect();
+                let prior = self.capture(schema, table, hash_values.clone()).await?;
+                let response = self
+                    .send(&json!({ "operation": "delete", "schema": schema, "table": table, "hash_values": hash_values }))
+                    .await?;
+                let deleted = touched(&response, "deleted_hashes", || hash_values.clone());
+
+                let restore: Vec<Value> = prior
+                    .into_iter()
+                    .filter(|record| deleted.iter().any(|hash| same_hash(hash, &record[hash_attribute])))
+                    .map(without_timestamps)
+                    .collect();
+                let compensation = if restore.is_empty() {
+                    None
+                } else {
+                    Some(json!({ "operation": "insert", "schema": schema, "table": table, "records": restore }))
+                };
+                Ok((response, compensation))
+            }
+        }
+    }
+
+    /// The current records for `hash_values`, with all attributes
+    async fn capture(&self, schema: &str, table: &str, hash_values: Vec<Value>) -> Result<Vec<Value>, Error> {
+        if hash_values.is_empty() {
+            return Ok(Vec::new());
+        }
+        let records = self
+            .send(&json!({
+                "operation": "search_by_hash",
+                "schema": schema,
+                "table": table,
+                "hash_values": hash_values,
+                "get_attributes": ["*"],
+            }))
+            .await?;
+        match records {
+            Value::Array(records) => Ok(records),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    async fn send(&self, operation: &Value) -> Result<Value, Error> {
+        let res = Error::check(self.harper.request(operation).await?).await?;
+        Ok(res.json().await?)
+    }
+}
+
+/// The hashes a step reports it changed, or `fallback` on servers that do not
+/// report them
+fn touched(response: &Value, field: &str, fallback: impl FnOnce() -> Vec<Value>) -> Vec<Value> {
+    match response.get(field).and_then(Value::as_array) {
+        Some(hashes) => hashes.clone(),
+        None => fallback(),
+    }
+}
+
+fn hashes(records: &Value, hash_attribute: &str) -> Vec<Value> {
+    records
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|record| record.get(hash_attribute))
+        .filter(|hash| !hash.is_null())
+        .cloned()
+        .collect()
+}
+
+/// Responses list hashes as strings even for numeric hash attributes.
+fn same_hash(a: &Value, b: &Value) -> bool {
+    let text = |value: &Value| match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    !a.is_null() && text(a) == text(b)
+}
+
+/// HarperDB maintains `__createdtime__` and `__updatedtime__` itself.
+fn without_timestamps(mut record: Value) -> Value {
+    if let Value::Object(fields) = &mut record {
+        fields.remove("__createdtime__");
+        fields.remove("__updatedtime__");
+    }
+    record
+}
@@ -0,0 +1,411 @@
+//! `harperdb`, a command-line client for the HarperDB operations API
+//!
+//! Connection settings come from a profile of a TOML config file (see
+//! `HarperConfig::from_file`) when `--profile` is given, and from the
+//! `HARPERDB_*` environment variables (see `HarperConfig::from_env`) otherwise.
+//!
+//! ```text
+//! harperdb --profile dev schema create dev
+//! harperdb --profile dev table create dev.dog --hash-attribute id
+//! harperdb --profile dev insert --table dev.dog --file dogs.json
+//! harperdb --profile dev --format csv sql "SELECT * FROM dev.dog"
+//! ```
+
+mod output;
+mod shell;
+
+use futures_util::stream::StreamExt;
+use harperdb as harper;
+use harperdb::middleware::DryRun;
+use harperdb::{Harper, HarperConfig, LogLevel, LogsOptions, Timestamp};
+use output::{Body, Format};
+use rustyline::completion::Completer;
+use rustyline::config::Configurer;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{ColorMode, Editor, Helper};
+use serde::Serialize;
+use serde_json::Value;
+use std::borrow::Cow;
+use std::error::Error;
+use std::path::PathBuf;
+use std::process;
+use std::str::FromStr;
+use std::time::Duration;
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+#[structopt(name = "harperdb", about = "Command-line client for HarperDB")]
+struct Cli {
+    /// Config file holding the connection profiles
+    #[structopt(long, default_value = "harperdb.toml", env = "HARPERDB_CONFIG", parse(from_os_str))]
+    config: PathBuf,
+    /// Profile of the config file to connect with. Without one, the connection
+    /// is read from HARPERDB_URL, HARPERDB_USERNAME and HARPERDB_PASSWORD.
+    #[structopt(long, short, env = "HARPERDB_PROFILE")]
+    profile: Option<String>,
+    /// Output format: json, table or csv
+    #[structopt(long, short, default_value = "table")]
+    format: Format,
+    /// Append the operations to this NDJSON file instead of sending them; see
+    /// `replay`
+    #[structopt(long, parse(from_os_str))]
+    dry_run: Option<PathBuf>,
+    #[structopt(subcommand)]
+    command: Command,
+}
+
+#[derive(StructOpt)]
+enum Command {
+    /// Create, drop and describe schemas
+    Schema(SchemaCommand),
+    /// Create, drop and describe tables
+    Table(TableCommand),
+    /// Insert the records of a JSON file holding an object or an array of objects
+    Insert {
+        /// `schema.table`, or a table of the profile's default schema
+        #[structopt(long)]
+        table: TableName,
+        #[structopt(long, parse(from_os_str))]
+        file: PathBuf,
+    },
+    /// Run a SQL statement
+    Sql { query: String },
+    /// Manage users
+    User(UserCommand),
+    /// Manage roles
+    Role(RoleCommand),
+    /// Read the server log
+    Logs {
+        #[structopt(long, default_value = "100")]
+        limit: usize,
+        /// error, warn, info, debug, trace, fatal or notify
+        #[structopt(long, parse(try_from_str = parse_level))]
+        level: Option<LogLevel>,
+        /// Oldest entry, e.g. 2020-09-01 or 2020-09-01T12:00:00Z
+        #[structopt(long)]
+        from: Option<Timestamp>,
+        /// Newest entry, in the same format as --from
+        #[structopt(long)]
+        until: Option<Timestamp>,
+        /// Keep polling for new entries, like `tail -f`
+        #[structopt(long)]
+        follow: bool,
+    },
+    /// Inspect jobs
+    Job(JobCommand),
+    /// Inspect the cluster
+    Cluster(ClusterCommand),
+    /// Start an interactive SQL shell
+    Shell,
+    /// Send the operations recorded with --dry-run, stopping at the first failure
+    Replay {
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+    },
+}
+
+#[derive(StructOpt)]
+enum SchemaCommand {
+    /// Create a schema
+    Create { name: String },
+    /// Drop a schema and all of its tables
+    Drop { name: String },
+    /// Show the tables of a schema
+    Describe { name: String },
+}
+
+#[derive(StructOpt)]
+enum TableCommand {
+    /// Create a table
+    Create {
+        /// `schema.table`, or a table of the profile's default schema
+        table: TableName,
+        #[structopt(long, default_value = "id")]
+        hash_attribute: String,
+    },
+    /// Drop a table and its records
+    Drop { table: TableName },
+    /// Show the attributes and record count of a table
+    Describe { table: TableName },
+}
+
+#[derive(StructOpt)]
+enum UserCommand {
+    /// Add a user. The password is read from HARPERDB_NEW_PASSWORD, or
+    /// prompted for.
+    Add {
+        username: String,
+        #[structopt(long)]
+        role: String,
+        /// Create the user disabled
+        #[structopt(long)]
+        inactive: bool,
+    },
+    /// Drop a user
+    Drop { username: String },
+    /// List the users with their roles
+    List,
+}
+
+#[derive(StructOpt)]
+enum RoleCommand {
+    /// List the roles with their permissions
+    List,
+}
+
+#[derive(StructOpt)]
+enum JobCommand {
+    /// Show the status and result of a job
+    Get { id: String },
+}
+
+#[derive(StructOpt)]
+enum ClusterCommand {
+    /// Show the connection state of the other cluster nodes
+    Status,
+}
+
+/// A table argument, either `schema.table` or a bare table name
+struct TableName {
+    schema: Option<String>,
+    table: String,
+}
+
+impl FromStr for TableName {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (schema, table) = match s.find('.') {
+            Some(dot) => (Some(s[..dot].to_string()), &s[dot + 1..]),
+            None => (None, s),
+        };
+        if table.is_empty() || schema.as_deref() == Some("") {
+            return Err(format!("invalid table '{}', expected schema.table or table", s));
+        }
+        Ok(TableName {
+            schema,
+            table: table.to_string(),
+        })
+    }
+}
+
+impl TableName {
+    /// The schema and table, falling back to the configured default schema
+    fn resolve(self, config: &HarperConfig) -> Result<(String, String), String> {
+        let schema = match self.schema {
+            Some(schema) => schema,
+            None if !config.schema.is_empty() => config.schema.clone(),
+            None => return Err(format!("no schema given for table '{}' and no default schema configured", self.table)),
+        };
+        Ok((schema, self.table))
+    }
+}
+
+fn parse_level(level: &str) -> Result<LogLevel, String> {
+    match serde_json::from_value(Value::String(level.to_lowercase())) {
+        Ok(LogLevel::Unknown) | Err(_) => Err(format!("unknown log level '{}'", level)),
+        Ok(level) => Ok(level),
+    }
+}
+
+/// Masks what is typed at the password prompt
+struct MaskedInput;
+
+impl Completer for MaskedInput {
+    type Candidate = String;
+}
+
+impl Hinter for MaskedInput {
+    type Hint = String;
+}
+
+impl Highlighter for MaskedInput {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        Cow::Owned("*".repeat(line.chars().count()))
+    }
+
+    fn highlight_char(&self, _line: &str, _pos: usize) -> bool {
+        true
+    }
+}
+
+impl Validator for MaskedInput {}
+
+impl Helper for MaskedInput {}
+
+/// The password of a new user, from HARPERDB_NEW_PASSWORD or a masked prompt,
+/// so it never appears in the process arguments or the shell history
+fn new_password() -> Result<String, Box<dyn Error>> {
+    if let Ok(password) = std::env::var("HARPERDB_NEW_PASSWORD") {
+        return Ok(password);
+    }
+    let mut editor = Editor::<MaskedInput>::new();
+    editor.set_helper(Some(MaskedInput));
+    editor.set_color_mode(ColorMode::Forced);
+    editor.set_auto_add_history(false);
+    Ok(editor.readline("Password: ")?)
+}
+
+/// A row of `user list`
+#[derive(Serialize)]
+struct UserRow {
+    username: String,
+    role: Option<String>,
+    active: bool,
+}
+
+#[tokio::main(basic_scheduler)]
+async fn main() {
+    let cli = Cli::from_args();
+    if let Err(error) = run(cli).await {
+        eprintln!("error: {}", error);
+        process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let config = match &cli.profile {
+        Some(profile) => HarperConfig::from_file(&cli.config, profile)?,
+        None => HarperConfig::from_env()?,
+    };
+    let mut harper_client = Harper::try_new(config.clone())?;
+    if let Some(file) = &cli.dry_run {
+        harper_client = harper_client.dry_run(DryRun::append_to_file(file)?);
+    }
+    let format = cli.format;
+
+    let res = match cli.command {
+        Command::Schema(SchemaCommand::Create { name }) => {
+            harper_client.create_schema(harper::SchemaOption { schema: name }).await?
+        }
+        Command::Schema(SchemaCommand::Drop { name }) => {
+            harper_client.drop_schema(harper::SchemaOption { schema: name }).await?
+        }
+        Command::Schema(SchemaCommand::Describe { name }) => {
+            harper_client.describe_schema(harper::SchemaOption { schema: name }).await?
+        }
+        Command::Table(TableCommand::Create { table, hash_attribute }) => {
+            let (schema, table) = table.resolve(&config)?;
+            let create_table_option = harper::CreateTableOptions {
+                schema,
+                table,
+                hash_attribute,
+            };
+            harper_client.create_table(create_table_option).await?
+        }
+        Command::Table(TableCommand::Drop { table }) => {
+            let (schema, table) = table.resolve(&config)?;
+            harper_client.drop_table(harper::TableOptions { schema, table }).await?
+        }
+        Command::Table(TableCommand::Describe { table }) => {
+            let (schema, table) = table.resolve(&config)?;
+            harper_client.describe_table(harper::TableOptions { schema, table }).await?
+        }
+        Command::Insert { table, file } => {
+            let (schema, table) = table.resolve(&config)?;
+            let contents = std::fs::read_to_string(&file)
+                .map_err(|error| format!("cannot read {}: {}", file.display(), error))?;
+            let records = match serde_json::from_str(&contents)? {
+                Value::Array(records) => Value::Array(records),
+                record @ Value::Object(_) => Value::Array(vec![record]),
+                _ => return Err(format!("{} must hold a JSON object or array of objects", file.display()).into()),
+            };
+            let insert_option = harper::QueryOptions { schema, table, records };
+            harper_client.insert(insert_option).await?
+        }
+        Command::Sql { query } => harper_client.query(&query).await?,
+        Command::User(UserCommand::Add { username, role, inactive }) => {
+            let user_option = harper::UserAddOptions {
+                role,
+                username,
+                password: new_password()?,
+                active: !inactive,
+            };
+            harper_client.add_user(user_option).await?
+        }
+        Command::User(UserCommand::Drop { username }) => {
+            let user_option = harper::UserDropOptions { username };
+            harper_client.drop_user(user_option).await?
+        }
+        Command::User(UserCommand::List) => {
+            let users: Vec<UserRow> = harper_client
+                .list_users()
+                .await?
+                .into_iter()
+                .map(|user| UserRow {
+                    username: user.username,
+                    role: user.role.map(|role| role.role),
+                    active: user.active,
+                })
+                .collect();
+            return output::print(&Body::of(&users)?, format);
+        }
+        Command::Role(RoleCommand::List) => harper_client.list_roles().await?,
+        Command::Logs {
+            limit,
+            level,
+            from,
+            until,
+            follow,
+        } => {
+            let logs_option = LogsOptions {
+                limit: Some(limit),
+                start: None,
+                level,
+                from,
+                until,
+                order: None,
+            };
+            return print_logs(&harper_client, logs_option, follow, format).await;
+        }
+        Command::Job(JobCommand::Get { id }) => {
+            harper_client.get_job(harper::GetJobOptions { id }).await?
+        }
+        Command::Cluster(ClusterCommand::Status) => harper_client.cluster_status().await?,
+        Command::Shell => return shell::run(&harper_client, &config.schema).await,
+        Command::Replay { file } => {
+            let responses = harper_client.replay(&DryRun::load(file)?).await?;
+            return output::print(&Body::of(&responses)?, format);
+        }
+    };
+
+    output::print(&output::response_body(res).await?, format)
+}
+
+async fn print_logs(
+    harper_client: &Harper,
+    logs_option: LogsOptions,
+    follow: bool,
+    format: Format,
+) -> Result<(), Box<dyn Error>> {
+    if !follow {
+        let entries = harper_client.read_logs(logs_option).await?;
+        return output::print(&Body::of(&entries)?, format);
+    }
+
+    // CSV gets one header, then a row per entry, flushed as entries arrive.
+    let mut csv_writer = csv::Writer::from_writer(std::io::stdout());
+    let mut header_written = false;
+    let mut logs = Box::pin(harper_client.follow_logs(logs_option, Duration::from_secs(1)));
+    while let Some(entry) = logs.next().await {
+        let entry = entry?;
+        match format {
+            Format::Json => println!("{}", serde_json::to_string(&entry)?),
+            Format::Table => println!("{} {:?} {}", entry.timestamp, entry.level, entry.message),
+            Format::Csv => {
+                let rows = Body::of(&[&entry])?.rows();
+                if !header_written {
+                    csv_writer.write_record(&rows.columns)?;
+                    header_written = true;
+                }
+                for row in &rows.rows {
+                    csv_writer.write_record(row)?;
+                }
+                csv_writer.flush()?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,268 @@
+use serde::de::{Deserializer, IgnoredAny, MapAccess, Visitor};
+use serde::ser::{SerializeMap, SerializeSeq, Serializer};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Table,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Format::Json),
+            "table" => Ok(Format::Table),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("unknown format '{}', expected json, table or csv", s)),
+        }
+    }
+}
+
+/// A body to print, with its keys in the order they were sent
+///
+/// `serde_json::Map` sorts its keys, so the order is read from the JSON text
+/// separately: the union of the keys of an array of objects, in first-seen
+/// order, or the keys of an object.
+pub struct Body {
+    pub value: Value,
+    pub keys: Vec<String>,
+}
+
+impl Body {
+    pub fn parse(text: &str) -> serde_json::Result<Self> {
+        Ok(Body {
+            value: serde_json::from_str(text)?,
+            keys: key_order(text),
+        })
+    }
+
+    /// Serialize `value`, keeping the order of its fields
+    pub fn of<T: Serialize>(value: &T) -> serde_json::Result<Self> {
+        Body::parse(&serde_json::to_string(value)?)
+    }
+
+    pub fn rows(&self) -> Rows {
+        Rows::from_ordered(&self.value, &self.keys)
+    }
+}
+
+fn key_order(text: &str) -> Vec<String> {
+    // Untagged enums buffer objects with their keys in order.
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Shape {
+        Objects(Vec<Keys>),
+        Object(Keys),
+        Other(IgnoredAny),
+    }
+
+    match serde_json::from_str(text) {
+        Ok(Shape::Objects(objects)) => {
+            let mut keys: Vec<String> = Vec::new();
+            for key in objects.into_iter().flat_map(|Keys(object)| object) {
+                if !keys.contains(&key) {
+                    keys.push(key);
+                }
+            }
+            keys
+        }
+        Ok(Shape::Object(Keys(keys))) => keys,
+        Ok(Shape::Other(_)) | Err(_) => Vec::new(),
+    }
+}
+
+/// The keys of a JSON object, in order
+struct Keys(Vec<String>);
+
+impl<'de> Deserialize<'de> for Keys {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeysVisitor;
+
+        impl<'de> Visitor<'de> for KeysVisitor {
+            type Value = Keys;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("an object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Keys, A::Error> {
+                let mut keys = Vec::new();
+                while let Some(key) = map.next_key::<String>()? {
+                    map.next_value::<IgnoredAny>()?;
+                    keys.push(key);
+                }
+                Ok(Keys(keys))
+            }
+        }
+
+        deserializer.deserialize_map(KeysVisitor)
+    }
+}
+
+/// Sort `keys` by their position in `order`; keys missing from it keep their
+/// relative order after the others
+fn ordered<'a, S: AsRef<str>>(keys: impl Iterator<Item = &'a String>, order: &[S]) -> Vec<String> {
+    let mut keys: Vec<String> = keys.cloned().collect();
+    keys.sort_by_key(|key| order.iter().position(|name| name.as_ref() == key).unwrap_or(order.len()));
+    keys
+}
+
+/// Serializes a value with the object keys listed in `order` first
+struct Ordered<'a, S>(&'a Value, &'a [S]);
+
+impl<S: AsRef<str>> Serialize for Ordered<'_, S> {
+    fn serialize<T: Serializer>(&self, serializer: T) -> Result<T::Ok, T::Error> {
+        match self.0 {
+            Value::Array(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(&Ordered(item, self.1))?;
+                }
+                seq.end()
+            }
+            Value::Object(object) => {
+                let mut map = serializer.serialize_map(Some(object.len()))?;
+                for key in ordered(object.keys(), self.1) {
+                    map.serialize_entry(&key, &object[&key])?;
+                }
+                map.end()
+            }
+            value => value.serialize(serializer),
+        }
+    }
+}
+
+/// A response body flattened into columns and rows of cell text
+pub struct Rows {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Rows {
+    /// An array of objects becomes one row per object with the union of their
+    /// keys as columns. Any other array becomes a single `value` column, and an
+    /// object becomes `key`/`value` pairs.
+    pub fn from_value(value: &Value) -> Self {
+        Rows::from_ordered::<&str>(value, &[])
+    }
+
+    /// Like `from_value`, with the columns or keys listed in `order` first
+    pub fn from_ordered<S: AsRef<str>>(value: &Value, order: &[S]) -> Self {
+        match value {
+            Value::Array(items) if items.iter().all(Value::is_object) => {
+                let mut columns: Vec<String> = Vec::new();
+                for item in items.iter().filter_map(Value::as_object) {
+                    for key in item.keys() {
+                        if !columns.contains(key) {
+                            columns.push(key.clone());
+                        }
+                    }
+                }
+                let columns = ordered(columns.iter(), order);
+                let rows = items
+                    .iter()
+                    .map(|item| columns.iter().map(|column| cell(&item[column])).collect())
+                    .collect();
+                Rows { columns, rows }
+            }
+            Value::Array(items) => Rows {
+                columns: vec!["value".to_string()],
+                rows: items.iter().map(|item| vec![cell(item)]).collect(),
+            },
+            Value::Object(map) => Rows {
+                columns: vec!["key".to_string(), "value".to_string()],
+                rows: ordered(map.keys(), order)
+                    .into_iter()
+                    .map(|key| {
+                        let value = cell(&map[&key]);
+                        vec![key, value]
+                    })
+                    .collect(),
+            },
+            _ => Rows {
+                columns: vec!["value".to_string()],
+                rows: vec![vec![cell(value)]],
+            },
+        }
+    }
+}
+
+/// Strings print without quotes, `null` as an empty cell and nested values as
+/// compact JSON.
+pub fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Columns padded to their widest cell, with a rule under the header
+impl fmt::Display for Rows {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut widths: Vec<usize> = self.columns.iter().map(|column| column.chars().count()).collect();
+        for row in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let line = |f: &mut fmt::Formatter<'_>, cells: &[String]| {
+            let padded: Vec<String> = cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:width$}", cell, width = width))
+                .collect();
+            writeln!(f, "{}", padded.join(" | ").trim_end())
+        };
+        line(f, &self.columns)?;
+        let rule: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+        writeln!(f, "{}", rule.join("-+-"))?;
+        for row in &self.rows {
+            line(f, row)?;
+        }
+        Ok(())
+    }
+}
+
+/// The JSON body of a successful response, or the server's error message
+pub async fn response_body(res: reqwest::Response) -> Result<Body, Box<dyn std::error::Error>> {
+    let status = res.status();
+    let text = res.text().await?;
+    let body = Body::parse(&text).unwrap_or(Body {
+        value: Value::String(text),
+        keys: Vec::new(),
+    });
+    if status.is_success() {
+        return Ok(body);
+    }
+    let message = match body.value.get("error") {
+        Some(error) => cell(error),
+        None => cell(&body.value),
+    };
+    Err(format!("{}: {}", status, message).into())
+}
+
+pub fn print(body: &Body, format: Format) -> Result<(), Box<dyn std::error::Error>> {
+    match format {
+        Format::Json => println!("{}", serde_json::to_string_pretty(&Ordered(&body.value, &body.keys))?),
+        Format::Table => print!("{}", body.rows()),
+        Format::Csv => {
+            let rows = body.rows();
+            let mut writer = csv::Writer::from_writer(std::io::stdout());
+            writer.write_record(&rows.columns)?;
+            for row in &rows.rows {
+                writer.write_record(row)?;
+            }
+            writer.flush()?;
+        }
+    }
+    Ok(())
+}
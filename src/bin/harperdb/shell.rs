@@ -0,0 +1,339 @@
+//! `harperdb shell`, an interactive SQL prompt
+//!
+//! Statements end with `;` and may span lines. Lines starting with a backslash
+//! are meta-commands, see `\?`.
+
+use crate::output::{cell, response_body, Rows};
+use harperdb::{Harper, TableOptions};
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::path::PathBuf;
+use std::time::Instant;
+
+const HELP: &str = "\
+Statements end with a semicolon and may span several lines.
+
+\\d              list the tables of all schemas
+\\d TABLE        describe a table, as schema.table or a table of the default schema
+\\c SCHEMA       change the default schema
+\\timing         toggle reporting how long each statement takes
+\\refresh        reload the schema, table and attribute names used for completion
+\\?              show this help
+\\q              quit";
+
+const KEYWORDS: &[&str] = &[
+    "AND",
+    "AS",
+    "ASC",
+    "AVG",
+    "BETWEEN",
+    "BY",
+    "COUNT",
+    "DELETE",
+    "DESC",
+    "DISTINCT",
+    "FROM",
+    "GROUP",
+    "HAVING",
+    "IN",
+    "INNER",
+    "INSERT",
+    "INTO",
+    "IS",
+    "JOIN",
+    "LEFT",
+    "LIKE",
+    "LIMIT",
+    "MAX",
+    "MIN",
+    "NOT",
+    "NULL",
+    "OFFSET",
+    "ON",
+    "OR",
+    "ORDER",
+    "OUTER",
+    "RIGHT",
+    "SEARCH_JSON",
+    "SELECT",
+    "SET",
+    "SUM",
+    "UPDATE",
+    "VALUES",
+    "WHERE",
+];
+
+/// Completes SQL keywords and the names found by `describe_all`
+#[derive(Default)]
+struct ShellHelper {
+    // Schemas, tables, `schema.table` pairs and attributes, sorted.
+    names: BTreeSet<String>,
+}
+
+impl ShellHelper {
+    fn set_catalog(&mut self, catalog: &Value) {
+        self.names.clear();
+        for (schema, tables) in catalog.as_object().into_iter().flatten() {
+            self.names.insert(schema.clone());
+            for (table, description) in tables.as_object().into_iter().flatten() {
+                self.names.insert(table.clone());
+                self.names.insert(format!("{}.{}", schema, table));
+                let attributes = description["attributes"].as_array().into_iter().flatten();
+                for attribute in attributes.filter_map(|attribute| attribute["attribute"].as_str())
+                {
+                    self.names.insert(attribute.to_string());
+                }
+            }
+        }
+    }
+}
+
+impl Completer for ShellHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let start = line[..pos]
+            .rfind(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+            .map_or(0, |i| i + 1);
+        let word = &line[start..pos];
+        if word.is_empty() {
+            return Ok((start, Vec::new()));
+        }
+
+        let mut candidates: Vec<String> = self
+            .names
+            .range(word.to_string()..)
+            .take_while(|name| name.starts_with(word))
+            .cloned()
+            .collect();
+        // Keywords follow the case of what was typed so far.
+        let lowercase = word.starts_with(|c: char| c.is_lowercase());
+        candidates.extend(
+            KEYWORDS
+                .iter()
+                .filter(|keyword| keyword.starts_with(&word.to_uppercase()))
+                .map(|keyword| {
+                    if lowercase {
+                        keyword.to_lowercase()
+                    } else {
+                        keyword.to_string()
+                    }
+                }),
+        );
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
+
+struct Shell<'a> {
+    harper_client: &'a Harper,
+    schema: String,
+    timing: bool,
+}
+
+/// Run the prompt until `\q` or end of input
+pub async fn run(harper_client: &Harper, schema: &str) -> Result<(), Box<dyn Error>> {
+    let mut editor = Editor::<ShellHelper>::new();
+    editor.set_helper(Some(ShellHelper::default()));
+    let history =
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".harperdb_history"));
+    if let Some(history) = &history {
+        // A missing history file just means a first session.
+        let _ = editor.load_history(history);
+    }
+
+    let mut shell = Shell {
+        harper_client,
+        schema: schema.to_string(),
+        timing: false,
+    };
+    shell.refresh(&mut editor).await;
+    println!("Type \\? for help, \\q to quit.");
+
+    let mut statement = String::new();
+    loop {
+        let prompt_schema = if shell.schema.is_empty() {
+            "harperdb"
+        } else {
+            &shell.schema
+        };
+        let prompt = format!(
+            "{}{} ",
+            prompt_schema,
+            if statement.is_empty() { "=>" } else { "->" }
+        );
+        let line = match editor.readline(&prompt) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => {
+                statement.clear();
+                continue;
+            }
+            Err(ReadlineError::Eof) => break,
+            Err(error) => return Err(error.into()),
+        };
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        editor.add_history_entry(trimmed);
+
+        if statement.is_empty() && trimmed.starts_with('\\') {
+            let mut words = trimmed.split_whitespace();
+            let command = words.next().unwrap_or_default();
+            let argument = words.next();
+            match command {
+                "\\q" => break,
+                "\\?" => println!("{}", HELP),
+                "\\timing" => {
+                    shell.timing = !shell.timing;
+                    println!("Timing is {}.", if shell.timing { "on" } else { "off" });
+                }
+                "\\c" => match argument {
+                    Some(schema) => shell.schema = schema.to_string(),
+                    None => println!("usage: \\c SCHEMA"),
+                },
+                "\\refresh" => shell.refresh(&mut editor).await,
+                "\\d" => {
+                    let result = match argument {
+                        Some(table) => shell.describe_table(table).await,
+                        None => shell.list_tables(&mut editor).await,
+                    };
+                    if let Err(error) = result {
+                        println!("ERROR: {}", error);
+                    }
+                }
+                _ => println!("unknown command {}, type \\? for help", command),
+            }
+            continue;
+        }
+
+        if !statement.is_empty() {
+            statement.push('\n');
+        }
+        statement.push_str(trimmed);
+        if let Some(sql) = statement.strip_suffix(';') {
+            if let Err(error) = shell.execute(sql).await {
+                println!("ERROR: {}", error);
+            }
+            statement.clear();
+        }
+    }
+
+    if let Some(history) = &history {
+        editor.save_history(history)?;
+    }
+    Ok(())
+}
+
+impl Shell<'_> {
+    async fn execute(&self, sql: &str) -> Result<(), Box<dyn Error>> {
+        let started = Instant::now();
+        let body = response_body(self.harper_client.query(sql).await?).await?;
+        let elapsed = started.elapsed();
+
+        match &body.value {
+            Value::Array(records) => {
+                print!("{}", body.rows());
+                println!(
+                    "({} row{})",
+                    records.len(),
+                    if records.len() == 1 { "" } else { "s" }
+                );
+            }
+            Value::Object(map) if map.contains_key("message") => {
+                println!("{}", cell(&map["message"]))
+            }
+            _ => print!("{}", body.rows()),
+        }
+        if self.timing {
+            println!("Time: {:.3} ms", elapsed.as_secs_f64() * 1000.0);
+        }
+        println!();
+        Ok(())
+    }
+
+    async fn describe_table(&self, name: &str) -> Result<(), Box<dyn Error>> {
+        let (schema, table) = match name.find('.') {
+            Some(dot) => (name[..dot].to_string(), name[dot + 1..].to_string()),
+            None if !self.schema.is_empty() => (self.schema.clone(), name.to_string()),
+            None => return Err("no default schema, use \\d schema.table or \\c SCHEMA".into()),
+        };
+        let table_option = TableOptions {
+            schema: schema.clone(),
+            table: table.clone(),
+        };
+        let description = response_body(self.harper_client.describe_table(table_option).await?)
+            .await?
+            .value;
+
+        println!(
+            "Table \"{}.{}\", hash attribute {}, {} records",
+            schema,
+            table,
+            cell(&description["hash_attribute"]),
+            cell(&description["record_count"]),
+        );
+        print!("{}", Rows::from_value(&description["attributes"]));
+        println!();
+        Ok(())
+    }
+
+    async fn list_tables(&self, editor: &mut Editor<ShellHelper>) -> Result<(), Box<dyn Error>> {
+        let catalog = response_body(self.harper_client.describe_all().await?)
+            .await?
+            .value;
+        if let Some(helper) = editor.helper_mut() {
+            helper.set_catalog(&catalog);
+        }
+
+        let mut tables = Vec::new();
+        for (schema, schema_tables) in catalog.as_object().into_iter().flatten() {
+            for (table, description) in schema_tables.as_object().into_iter().flatten() {
+                tables.push(json!({
+                    "schema": schema,
+                    "table": table,
+                    "hash_attribute": description["hash_attribute"],
+                    "record_count": description["record_count"],
+                }));
+            }
+        }
+        let columns = ["schema", "table", "hash_attribute", "record_count"];
+        print!("{}", Rows::from_ordered(&Value::Array(tables), &columns));
+        println!();
+        Ok(())
+    }
+
+    /// Reload the completion names; failures only cost completion.
+    async fn refresh(&self, editor: &mut Editor<ShellHelper>) {
+        let catalog = match self.harper_client.describe_all().await {
+            Ok(res) => response_body(res).await.map(|body| body.value),
+            Err(error) => Err(error.into()),
+        };
+        match (catalog, editor.helper_mut()) {
+            (Ok(catalog), Some(helper)) => helper.set_catalog(&catalog),
+            (Err(error), _) => println!("could not load table names for completion: {}", error),
+            _ => {}
+        }
+    }
+}
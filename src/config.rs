@@ -0,0 +1,278 @@
+use crate::middleware::RateLimits;
+use crate::nodes::NodeSelection;
+use crate::tls::TlsConfig;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HarperConfig {
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    /// Default schema of `Harper::table` and of the tables named without a
+    /// schema in the CLI and its shell. Operations that take a schema, raw
+    /// `query` statements and the `sql` builders never fall back to it.
+    pub schema: String,
+    /// Other cluster nodes to fail over to when `url` cannot be reached
+    pub nodes: Vec<String>,
+    pub node_selection: NodeSelection,
+    /// How often an unreachable node is probed until it answers again
+    pub health_check_interval: Duration,
+    /// Client-side concurrency and request rate limits
+    pub limits: RateLimits,
+    pub tls: TlsConfig,
+    pub timeouts: Timeouts,
+    /// How long `describe_*` results are served from memory; unset disables
+    /// the cache
+    pub metadata_ttl: Option<Duration>,
+}
+
+impl Default for HarperConfig {
+    fn default() -> Self {
+        HarperConfig {
+            url: "http://localhost:9925/".to_string(),
+            username: String::new(),
+            password: String::new(),
+            schema: String::new(),
+            nodes: Vec::new(),
+            node_selection: NodeSelection::default(),
+            health_check_interval: Duration::from_secs(5),
+            limits: RateLimits::default(),
+            tls: TlsConfig::default(),
+            timeouts: Timeouts::default(),
+            metadata_ttl: None,
+        }
+    }
+}
+
+/// How long the client waits before giving up with `Error::Timeout`
+///
+/// Request timeouts cover the whole exchange, from connecting until the response
+/// body has been read. `Harper::with_timeout` overrides them for single calls.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct Timeouts {
+    /// Establishing the TCP and TLS connection to a node
+    pub connect: Duration,
+    /// Operations without a more specific timeout below
+    pub request: Duration,
+    /// `csv_data_load`, `csv_file_load`, `csv_url_load`, `import_from_s3`,
+    /// `export_local`, `export_to_s3` and `Harper::query_stream`
+    pub bulk: Duration,
+    /// `search_by_hash`
+    pub lookup: Duration,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Timeouts {
+            connect: Duration::from_secs(10),
+            request: Duration::from_secs(60),
+            bulk: Duration::from_secs(600),
+            lookup: Duration::from_secs(10),
+        }
+    }
+}
+
+impl Timeouts {
+    /// The request timeout for an operation body
+    pub fn for_operation(&self, operation: &Value) -> Duration {
+        match operation.get("operation").and_then(Value::as_str).unwrap_or_default() {
+            "csv_data_load" | "csv_file_load" | "csv_url_load" | "import_from_s3" | "export_local"
+            | "export_to_s3" => self.bulk,
+            "search_by_hash" => self.lookup,
+            _ => self.request,
+        }
+    }
+}
+
+/// Errors from loading or validating a `HarperConfig`
+#[derive(Debug)]
+pub enum ConfigError {
+    /// A required setting is missing; names the variable or profile key
+    MissingField(String),
+    InvalidUrl { url: String, reason: String },
+    /// A `RateLimits` budget (`all`, `reads`, `writes` or `admin`) is invalid
+    InvalidLimit { budget: String, reason: String },
+    UnknownProfile { path: PathBuf, profile: String },
+    Io { path: PathBuf, error: std::io::Error },
+    Parse { path: PathBuf, error: toml::de::Error },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::MissingField(field) => write!(f, "missing required setting {}", field),
+            ConfigError::InvalidUrl { url, reason } => write!(f, "invalid HarperDB URL '{}': {}", url, reason),
+            ConfigError::InvalidLimit { budget, reason } => write!(f, "invalid {} rate limit: {}", budget, reason),
+            ConfigError::UnknownProfile { path, profile } => {
+                write!(f, "no profile '{}' in {}", profile, path.display())
+            }
+            ConfigError::Io { path, error } => write!(f, "cannot read {}: {}", path.display(), error),
+            ConfigError::Parse { path, error } => write!(f, "invalid config file {}: {}", path.display(), error),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io { error, .. } => Some(error),
+            ConfigError::Parse { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+// One named profile of a config file. Paths are relative to the file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Profile {
+    url: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+    schema: Option<String>,
+    #[serde(default)]
+    nodes: Vec<String>,
+    node_selection: Option<NodeSelection>,
+    #[serde(default)]
+    ca_certificates: Vec<PathBuf>,
+    pinned_certificate: Option<String>,
+    #[serde(default)]
+    danger_accept_invalid_certs: bool,
+}
+
+impl HarperConfig {
+    /// Read the configuration from `HARPERDB_URL`, `HARPERDB_USERNAME`,
+    /// `HARPERDB_PASSWORD` and the optional `HARPERDB_SCHEMA`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let harper_client = Harper::new(HarperConfig::from_env()?);
+    /// ```
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        let required = |name: &str| var(name).ok_or_else(|| ConfigError::MissingField(name.to_string()));
+
+        let config = HarperConfig {
+            url: required("HARPERDB_URL")?,
+            username: required("HARPERDB_USERNAME")?,
+            password: required("HARPERDB_PASSWORD")?,
+            schema: var("HARPERDB_SCHEMA").unwrap_or_default(),
+            ..Default::default()
+        };
+        config.validated()
+    }
+
+    /// Read one named profile from a TOML file
+    ///
+    /// ```toml
+    /// [dev]
+    /// url = "http://localhost:9925"
+    /// username = "HDB_ADMIN"
+    /// password = "password"
+    /// schema = "dev"
+    ///
+    /// [prod]
+    /// url = "https://hdb1.example.com:9926"
+    /// nodes = ["https://hdb2.example.com:9926"]
+    /// node_selection = "round_robin"
+    /// username = "deploy"
+    /// password = "secret"
+    /// ca_certificates = ["certs/internal-ca.pem"]
+    /// ```
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let config = HarperConfig::from_file("harperdb.toml", "staging")?;
+    /// ```
+    pub fn from_file<P: AsRef<Path>>(path: P, profile: &str) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let io_error = |error| ConfigError::Io {
+            path: path.to_path_buf(),
+            error,
+        };
+        let contents = std::fs::read_to_string(path).map_err(io_error)?;
+        let mut profiles: HashMap<String, Profile> = toml::from_str(&contents).map_err(|error| ConfigError::Parse {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        let selected = profiles.remove(profile).ok_or_else(|| ConfigError::UnknownProfile {
+            path: path.to_path_buf(),
+            profile: profile.to_string(),
+        })?;
+
+        let required = |value: Option<String>, field: &str| {
+            value.ok_or_else(|| ConfigError::MissingField(format!("{}.{} in {}", profile, field, path.display())))
+        };
+        let base = path.parent().unwrap_or_else(|| Path::new(""));
+        let root_certificates = selected
+            .ca_certificates
+            .iter()
+            .map(|certificate| std::fs::read(base.join(certificate)))
+            .collect::<Result<_, _>>()
+            .map_err(io_error)?;
+
+        let config = HarperConfig {
+            url: required(selected.url, "url")?,
+            username: required(selected.username, "username")?,
+            password: required(selected.password, "password")?,
+            schema: selected.schema.unwrap_or_default(),
+            nodes: selected.nodes,
+            node_selection: selected.node_selection.unwrap_or_default(),
+            tls: TlsConfig {
+                root_certificates,
+                pinned_certificate: selected.pinned_certificate,
+                danger_accept_invalid_certs: selected.danger_accept_invalid_certs,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        config.validated()
+    }
+
+    /// Check and normalize every node URL: a missing scheme defaults to
+    /// `http://`, and the path always ends with a slash. Also checks `limits`.
+    pub fn validated(mut self) -> Result<Self, ConfigError> {
+        self.url = normalize_url(&self.url)?;
+        for node in self.nodes.iter_mut() {
+            *node = normalize_url(node)?;
+        }
+        self.limits.validate()?;
+        Ok(self)
+    }
+}
+
+pub(crate) fn normalize_url(url: &str) -> Result<String, ConfigError> {
+    let invalid = |reason: &str| ConfigError::InvalidUrl {
+        url: url.to_string(),
+        reason: reason.to_string(),
+    };
+    let trimmed = url.trim();
+    if trimmed.is_empty() {
+        return Err(invalid("the URL is empty"));
+    }
+    let with_scheme = if trimmed.contains("://") {
+        trimmed.to_string()
+    } else {
+        format!("http://{}", trimmed)
+    };
+
+    let mut parsed = reqwest::Url::parse(&with_scheme).map_err(|error| invalid(&error.to_string()))?;
+    if parsed.scheme() != "http" && parsed.scheme() != "https" {
+        return Err(invalid("the scheme must be http or https"));
+    }
+    if parsed.host_str().is_none_or(str::is_empty) {
+        return Err(invalid("the URL has no host"));
+    }
+    if !parsed.path().ends_with('/') {
+        let path = format!("{}/", parsed.path());
+        parsed.set_path(&path);
+    }
+    Ok(parsed.to_string())
+}
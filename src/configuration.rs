@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fmt;
+
+/// The settings of a HarperDB server, from `Harper::get_configuration`
+///
+/// The commonly tuned settings are typed; every other key of each section is
+/// kept in its `other` map, so nothing the server reports is lost. Servers that
+/// report a flat configuration (HarperDB 3) fill only the top-level `other`.
+///
+/// Also used as the partial update of `Harper::set_configuration`: only the
+/// fields that are set are sent.
+///
+/// ```
+/// let update = ServerConfiguration {
+///     logging: Some(LoggingConfiguration {
+///         level: Some("warn".to_string()),
+///         ..Default::default()
+///     }),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerConfiguration {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threads: Option<Threads>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http: Option<HttpConfiguration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logging: Option<LoggingConfiguration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clustering: Option<ClusteringConfiguration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operations_api: Option<OperationsApiConfiguration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_functions: Option<CustomFunctionsConfiguration>,
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// The number of worker threads: a count, or a section holding the count
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum Threads {
+    Count(usize),
+    Settings {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        count: Option<usize>,
+        #[serde(flatten)]
+        other: Map<String, Value>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpConfiguration {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secure_port: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threads: Option<usize>,
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LoggingConfiguration {
+    /// `notify`, `fatal`, `error`, `warn`, `info`, `debug` or `trace`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stdstreams: Option<bool>,
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusteringConfiguration {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hub_server: Option<ClusteringServerConfiguration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub leaf_server: Option<ClusteringServerConfiguration>,
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// The hub or leaf server of clustering
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusteringServerConfiguration {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<NetworkConfiguration>,
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationsApiConfiguration {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<NetworkConfiguration>,
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomFunctionsConfiguration {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<NetworkConfiguration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkConfiguration {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// One setting an update would change
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigurationChange {
+    /// The setting as `set_configuration` names it, e.g. `logging_level`
+    pub key: String,
+    /// The current value; `None` when the server does not report the setting
+    pub from: Option<Value>,
+    pub to: Value,
+}
+
+impl fmt::Display for ConfigurationChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.from {
+            Some(from) => write!(f, "{}: {} -> {}", self.key, from, self.to),
+            None => write!(f, "{}: (unset) -> {}", self.key, self.to),
+        }
+    }
+}
+
+impl ServerConfiguration {
+    /// The settings as `set_configuration` takes them: one key per setting,
+    /// the path of sections joined with `_` (`clustering_hubServer_network_port`)
+    pub fn flatten(&self) -> Map<String, Value> {
+        let mut settings = Map::new();
+        if let Ok(Value::Object(sections)) = serde_json::to_value(self) {
+            flatten_into(&mut settings, None, sections);
+        }
+        settings
+    }
+
+    /// The settings of `update` that differ from this configuration, in key
+    /// order
+    pub fn diff(&self, update: &ServerConfiguration) -> Vec<ConfigurationChange> {
+        let current = self.flatten();
+        let mut changes: Vec<ConfigurationChange> = update
+            .flatten()
+            .into_iter()
+            .filter(|(key, to)| current.get(key) != Some(to))
+            .map(|(key, to)| ConfigurationChange {
+                from: current.get(&key).cloned(),
+                key,
+                to,
+            })
+            .collect();
+        changes.sort_by(|a, b| a.key.cmp(&b.key));
+        changes
+    }
+}
+
+fn flatten_into(settings: &mut Map<String, Value>, prefix: Option<&str>, sections: Map<String, Value>) {
+    for (name, value) in sections {
+        let key = match prefix {
+            Some(prefix) => format!("{}_{}", prefix, name),
+            None => name,
+        };
+        match value {
+            Value::Object(section) => flatten_into(settings, Some(&key), section),
+            value => {
+                settings.insert(key, value);
+            }
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! Serde helpers for HarperDB's epoch-millisecond timestamps, such as the
+//! `__createdtime__` and `__updatedtime__` attributes of every record.
+//!
+//! Values are read from epoch milliseconds (integer or float) or ISO 8601
+//! strings, and written as epoch milliseconds.
+//!
+//! ```
+//! #[derive(Debug, Serialize, Deserialize)]
+//! struct DogRecord {
+//!     id: usize,
+//!     name: String,
+//!     #[serde(with = "harperdb::epoch_millis::utc")]
+//!     __createdtime__: DateTime<Utc>,
+//!     #[serde(default, with = "harperdb::epoch_millis::utc_option")]
+//!     __updatedtime__: Option<DateTime<Utc>>,
+//! }
+//! ```
+
+use crate::timestamp::Timestamp;
+use serde::{de, Deserialize, Deserializer};
+
+fn out_of_range<E: de::Error>(timestamp: Timestamp) -> E {
+    E::custom(format!("timestamp {} is out of range", timestamp.as_millis()))
+}
+
+/// `DateTime<Utc>` as epoch milliseconds
+#[cfg(feature = "chrono")]
+pub mod utc {
+    use super::*;
+    use chrono::{DateTime, Utc};
+    use serde::Serializer;
+
+    pub fn serialize<S: Serializer>(time: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(time.timestamp_millis())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+        let timestamp = Timestamp::deserialize(deserializer)?;
+        timestamp.to_chrono().ok_or_else(|| out_of_range(timestamp))
+    }
+}
+
+/// `Option<DateTime<Utc>>` as epoch milliseconds or null
+#[cfg(feature = "chrono")]
+pub mod utc_option {
+    use super::*;
+    use chrono::{DateTime, Utc};
+    use serde::Serializer;
+
+    pub fn serialize<S: Serializer>(time: &Option<DateTime<Utc>>, serializer: S) -> Result<S::Ok, S::Error> {
+        match time {
+            Some(time) => serializer.serialize_some(&time.timestamp_millis()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error> {
+        match Option::<Timestamp>::deserialize(deserializer)? {
+            Some(timestamp) => timestamp.to_chrono().map(Some).ok_or_else(|| out_of_range(timestamp)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// `time::OffsetDateTime` as epoch milliseconds, read in UTC
+#[cfg(feature = "time")]
+pub mod offset_date_time {
+    use super::*;
+    use serde::Serializer;
+    use time::OffsetDateTime;
+
+    pub fn serialize<S: Serializer>(time: &OffsetDateTime, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(Timestamp::from(*time).as_millis())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<OffsetDateTime, D::Error> {
+        let timestamp = Timestamp::deserialize(deserializer)?;
+        timestamp.to_offset_date_time().ok_or_else(|| out_of_range(timestamp))
+    }
+}
+
+/// `Option<time::OffsetDateTime>` as epoch milliseconds or null, read in UTC
+#[cfg(feature = "time")]
+pub mod offset_date_time_option {
+    use super::*;
+    use serde::Serializer;
+    use time::OffsetDateTime;
+
+    pub fn serialize<S: Serializer>(time: &Option<OffsetDateTime>, serializer: S) -> Result<S::Ok, S::Error> {
+        match time {
+            Some(time) => serializer.serialize_some(&Timestamp::from(*time).as_millis()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<OffsetDateTime>, D::Error> {
+        match Option::<Timestamp>::deserialize(deserializer)? {
+            Some(timestamp) => timestamp.to_offset_date_time().map(Some).ok_or_else(|| out_of_range(timestamp)),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use crate::config::ConfigError;
+use std::fmt;
+use std::time::Duration;
+
+/// Errors returned by the HarperDB client
+#[derive(Debug)]
+pub enum Error {
+    /// The HTTP request failed or its response could not be decoded
+    Http(reqwest::Error),
+    /// Connecting, or waiting for the response, took longer than the timeout
+    Timeout(reqwest::Error),
+    /// The operation could not be encoded as JSON, or a response not decoded
+    Json(serde_json::Error),
+    /// The client configuration is incomplete or invalid
+    Config(ConfigError),
+    /// The TLS configuration is invalid or unsupported by the enabled features
+    Tls(String),
+    /// A middleware rejected the operation
+    Middleware(Box<dyn std::error::Error + Send + Sync>),
+    /// HarperDB answered with an error status, e.g. for a missing table
+    Operation { status: reqwest::StatusCode, message: String },
+    /// Reading or writing a local file failed
+    Io(std::io::Error),
+    /// HarperDB answered successfully, but not with what the operation returns,
+    /// e.g. no job id for an operation that starts a job
+    UnexpectedResponse(String),
+    /// `get_job` knows no job with this id
+    JobNotFound(String),
+    /// The job did not finish within the time given to `JobHandle::wait`
+    JobTimeout { id: String, waited: Duration },
+    /// The server did not answer within the time given to `wait_until_ready`
+    NotReady(Duration),
+    /// A token passed to `TokenClaims::decode` is not a JWT with a JSON payload
+    InvalidToken(String),
+    /// A statement from the `sql` builders cannot run, e.g. an `IN` without
+    /// values or an `INSERT` without records
+    Sql(String),
+    /// The desired users of `provision_users` cannot be applied, e.g. one
+    /// names an unknown role or a username is listed twice; found before any
+    /// change is made
+    Provision(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Http(error) => write!(f, "{}", error),
+            Error::Timeout(error) => write!(f, "timed out: {}", error),
+            Error::Json(error) => write!(f, "invalid JSON: {}", error),
+            Error::Config(error) => write!(f, "{}", error),
+            Error::Tls(message) => write!(f, "TLS configuration error: {}", message),
+            Error::Middleware(error) => write!(f, "middleware error: {}", error),
+            Error::Operation { status, message } => write!(f, "HarperDB returned {}: {}", status, message),
+            Error::Io(error) => write!(f, "I/O error: {}", error),
+            Error::UnexpectedResponse(message) => write!(f, "unexpected response: {}", message),
+            Error::JobNotFound(id) => write!(f, "job {} not found", id),
+            Error::JobTimeout { id, waited } => write!(f, "job {} did not finish within {:?}", id, waited),
+            Error::NotReady(waited) => write!(f, "HarperDB was not ready after {:?}", waited),
+            Error::InvalidToken(message) => write!(f, "invalid token: {}", message),
+            Error::Sql(message) => write!(f, "invalid SQL statement: {}", message),
+            Error::Provision(message) => write!(f, "cannot provision users: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Http(error) => Some(error),
+            Error::Timeout(error) => Some(error),
+            Error::Json(error) => Some(error),
+            Error::Config(error) => Some(error),
+            Error::Tls(_) => None,
+            Error::Middleware(error) => Some(error.as_ref()),
+            Error::Operation { .. } => None,
+            Error::Io(error) => Some(error),
+            Error::UnexpectedResponse(_) | Error::JobNotFound(_) | Error::JobTimeout { .. } => None,
+            Error::NotReady(_) | Error::InvalidToken(_) | Error::Sql(_) | Error::Provision(_) => None,
+        }
+    }
+}
+
+impl Error {
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, Error::Timeout(_))
+    }
+
+    /// True when the request never reached the node, so it is safe to send it
+    /// elsewhere
+    pub(crate) fn is_connect(&self) -> bool {
+        match self {
+            Error::Http(error) | Error::Timeout(error) => error.is_connect(),
+            _ => false,
+        }
+    }
+
+    /// Pass a successful response through, and turn an error status into
+    /// `Error::Operation` carrying the `error` message of the body.
+    pub(crate) async fn check(res: reqwest::Response) -> Result<reqwest::Response, Error> {
+        let status = res.status();
+        if status.is_success() {
+            return Ok(res);
+        }
+        let text = res.text().await?;
+        let message = match serde_json::from_str::<serde_json::Value>(&text) {
+            Ok(serde_json::Value::Object(body)) => match body.get("error") {
+                Some(serde_json::Value::String(message)) => message.clone(),
+                Some(error) => error.to_string(),
+                None => text,
+            },
+            _ => text,
+        };
+        Err(Error::Operation { status, message })
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            Error::Timeout(error)
+        } else {
+            Error::Http(error)
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<ConfigError> for Error {
+    fn from(error: ConfigError) -> Self {
+        Error::Config(error)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Error::Json(error)
+    }
+}
+
+/// A replay that stopped at a failing operation
+#[derive(Debug)]
+pub struct ReplayError {
+    /// Index of the failed operation; the ones before it were applied
+    pub index: usize,
+    pub error: Error,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "replayed operation {} failed after {} were applied: {}",
+            self.index, self.index, self.error
+        )
+    }
+}
+
+impl std::error::Error for ReplayError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
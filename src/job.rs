@@ -0,0 +1,127 @@
+use crate::error::Error;
+use crate::timestamp::Timestamp;
+use crate::Harper;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+/// A job the server started in the background, e.g. by `delete_records_before`
+///
+/// The operation only starts the job; `get` looks up its progress and `wait`
+/// polls until it finishes.
+#[derive(Clone)]
+pub struct JobHandle {
+    harper: Harper,
+    id: String,
+}
+
+/// A background job as `get_job` reports it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Job {
+    pub id: String,
+    /// The operation that started the job, e.g. `delete_records_before`
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    pub status: JobStatus,
+    pub user: Option<String>,
+    pub start_datetime: Option<Timestamp>,
+    pub end_datetime: Option<Timestamp>,
+    /// The job's result or error, a string or an object depending on the job
+    pub message: Option<Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum JobStatus {
+    Pending,
+    InProgress,
+    Complete,
+    Error,
+    /// A status this version of the crate does not know
+    #[serde(other)]
+    Unknown,
+}
+
+impl JobStatus {
+    /// True once the job completed or failed
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobStatus::Complete | JobStatus::Error)
+    }
+}
+
+impl JobHandle {
+    /// Read the job id from the response of an operation that starts a job:
+    /// `{"job_id": "..."}`, or `{"message": "Starting job with id ..."}` on older
+    /// servers.
+    pub(crate) async fn from_response(harper: &Harper, res: reqwest::Response) -> Result<Self, Error> {
+        let body: Value = Error::check(res).await?.json().await?;
+        let id = body["job_id"].as_str().map(str::to_string).or_else(|| {
+            let message = body["message"].as_str()?;
+            let (_, id) = message.rsplit_once("id ")?;
+            Some(id.trim().to_string())
+        });
+
+        match id {
+            Some(id) if !id.is_empty() => Ok(JobHandle {
+                harper: harper.clone(),
+                id,
+            }),
+            _ => Err(Error::UnexpectedResponse(format!("no job id in {}", body))),
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Get Job
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let job: Job = handle.get().await?;
+    /// ```
+    pub async fn get(&self) -> Result<Job, Error> {
+        let map = json!({
+            "operation": "get_job",
+            "id": &self.id,
+        });
+        let res = Error::check(self.harper.request(&map).await?).await?;
+
+        // The job comes back as the only element of an array.
+        let mut jobs: Vec<Job> = res.json().await?;
+        match jobs.pop() {
+            Some(job) => Ok(job),
+            None => Err(Error::JobNotFound(self.id.clone())),
+        }
+    }
+
+    /// Poll the job every `poll_interval` until it completes or fails, for at
+    /// most `timeout`. A failed job is returned too; check its `status`. Fails
+    /// with `Error::JobTimeout` when the job is still running at the deadline;
+    /// the job itself keeps running on the server.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let job: Job = handle.wait(Duration::from_secs(1), Duration::from_secs(600)).await?;
+    /// assert_eq!(job.status, JobStatus::Complete);
+    /// ```
+    pub async fn wait(&self, poll_interval: Duration, timeout: Duration) -> Result<Job, Error> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let job = self.get().await?;
+            if job.status.is_finished() {
+                return Ok(job);
+            }
+            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+            if remaining == Duration::from_secs(0) {
+                return Err(Error::JobTimeout {
+                    id: self.id.clone(),
+                    waited: timeout,
+                });
+            }
+            tokio::time::delay_for(remaining.min(poll_interval)).await;
+        }
+    }
+}
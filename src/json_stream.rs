@@ -0,0 +1,152 @@
+//! Splits a JSON array arriving in chunks into its elements.
+
+use crate::error::Error;
+
+enum State {
+    /// Before the opening `[`
+    Start,
+    /// After the opening `[`, expecting the first element or `]`
+    First,
+    /// After a `,`, expecting an element
+    Next,
+    /// Inside an element
+    Element { depth: usize, in_string: bool, escaped: bool },
+    /// After the closing `]`
+    End,
+}
+
+/// Only the element being decoded is buffered.
+pub(crate) struct ArrayDecoder {
+    state: State,
+    element: Vec<u8>,
+}
+
+impl ArrayDecoder {
+    pub(crate) fn new() -> Self {
+        ArrayDecoder {
+            state: State::Start,
+            element: Vec::new(),
+        }
+    }
+
+    /// Feed the next chunk of the body, returning the elements it completes
+    pub(crate) fn push(&mut self, chunk: &[u8]) -> Result<Vec<Vec<u8>>, Error> {
+        let mut elements = Vec::new();
+
+        for &byte in chunk {
+            match &mut self.state {
+                State::Start => match byte {
+                    b'[' => self.state = State::First,
+                    _ if byte.is_ascii_whitespace() => {}
+                    _ => return Err(invalid("expected a JSON array of rows")),
+                },
+                State::First | State::Next => match byte {
+                    _ if byte.is_ascii_whitespace() => {}
+                    b']' if matches!(self.state, State::First) => self.state = State::End,
+                    b',' | b']' => return Err(invalid("expected an array element")),
+                    _ => {
+                        self.element.push(byte);
+                        self.state = State::Element {
+                            depth: if byte == b'{' || byte == b'[' { 1 } else { 0 },
+                            in_string: byte == b'"',
+                            escaped: false,
+                        };
+                    }
+                },
+                State::Element {
+                    depth,
+                    in_string,
+                    escaped,
+                } => {
+                    if *in_string {
+                        if *escaped {
+                            *escaped = false;
+                        } else if byte == b'\\' {
+                            *escaped = true;
+                        } else if byte == b'"' {
+                            *in_string = false;
+                        }
+                    } else if *depth == 0 && (byte == b',' || byte == b']') {
+                        elements.push(std::mem::take(&mut self.element));
+                        self.state = if byte == b']' { State::End } else { State::Next };
+                        continue;
+                    } else {
+                        match byte {
+                            b'"' => *in_string = true,
+                            b'{' | b'[' => *depth += 1,
+                            b'}' | b']' => *depth = depth.saturating_sub(1),
+                            _ => {}
+                        }
+                    }
+                    self.element.push(byte);
+                }
+                State::End => {
+                    if !byte.is_ascii_whitespace() {
+                        return Err(invalid("unexpected data after the JSON array"));
+                    }
+                }
+            }
+        }
+
+        Ok(elements)
+    }
+
+    /// Check that the body ended with the closing `]`
+    pub(crate) fn finish(&self) -> Result<(), Error> {
+        match self.state {
+            State::End => Ok(()),
+            _ => Err(invalid("the JSON array ended early")),
+        }
+    }
+}
+
+fn invalid(message: &str) -> Error {
+    Error::Json(serde::de::Error::custom(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ArrayDecoder;
+
+    /// Feed `chunks` in order, returning the elements as strings
+    fn decode(chunks: &[&str]) -> Result<Vec<String>, crate::Error> {
+        let mut decoder = ArrayDecoder::new();
+        let mut elements = Vec::new();
+        for chunk in chunks {
+            elements.extend(decoder.push(chunk.as_bytes())?);
+        }
+        decoder.finish()?;
+        Ok(elements.into_iter().map(|element| String::from_utf8(element).unwrap()).collect())
+    }
+
+    #[test]
+    fn splits_elements() {
+        assert_eq!(decode(&["[]"]).unwrap(), Vec::<String>::new());
+        assert_eq!(decode(&[" [ 1, true ,null,\"a\"] "]).unwrap(), ["1", "true ", "null", "\"a\""]);
+        assert_eq!(decode(&[r#"[{"a":[1,2]},[3,{"b":4}]]"#]).unwrap(), [r#"{"a":[1,2]}"#, r#"[3,{"b":4}]"#]);
+    }
+
+    #[test]
+    fn elements_split_across_chunks() {
+        assert_eq!(decode(&["[{\"na", "me\":\"Pen", "ny\"},", "{\"id\"", ":2}", "]"]).unwrap(), [
+            r#"{"name":"Penny"}"#,
+            r#"{"id":2}"#
+        ]);
+        assert_eq!(decode(&["[", "12", "3,4", "5", "]"]).unwrap(), ["123", "45"]);
+        // An escape split from the quote it escapes
+        assert_eq!(decode(&["[\"a\\", "\"b\"]"]).unwrap(), [r#""a\"b""#]);
+    }
+
+    #[test]
+    fn strings_with_delimiters() {
+        let elements = decode(&[r#"[{"s":"], ,[{"},"\"],",{"t":"\\"}]"#]).unwrap();
+        assert_eq!(elements, [r#"{"s":"], ,[{"}"#, r#""\"],""#, r#"{"t":"\\"}"#]);
+    }
+
+    #[test]
+    fn rejects_malformed_arrays() {
+        for body in &["[,1]", "[1,,2]", "[1,]", "[,]", "1,,2", "{}", "[1] 2", "[1", "[1,"] {
+            assert!(decode(&[body]).is_err(), "{} was accepted", body);
+        }
+    }
+}
@@ -11,8 +11,8 @@
 //! Basic usage:
 //!
 //! ```
-//! use harperdb_sdk_rust::{ HarperConfig, Harper };
-//! use harperdb_sdk_rust as harper;
+//! use harperdb::{ HarperConfig, Harper };
+//! use harperdb as harper;
 //! use serde::{Deserialize, Serialize};
 //! use serde_json::{Value};
 //! use std::{error::Error};
@@ -34,18 +34,19 @@
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn Error>> {
 //!     let config: HarperConfig = HarperConfig {
-//!         url: "http://0.0.0.0:9925/",
-//!         username: "HDB_ADMIN",
-//!         password: "password",
-//!         schema: "dev",
+//!         url: "http://0.0.0.0:9925/".to_string(),
+//!         username: "HDB_ADMIN".to_string(),
+//!         password: "password".to_string(),
+//!         schema: "dev".to_string(),
+//!         ..Default::default()
 //!     };
 //!
 //!     let harper_client = new(config);
 //!     
 //!     // Insert Record
 //!     let insert_option: harper::QueryOptions = harper::QueryOptions {
-//!         table: "dog",
-//!         schema: "dev",
+//!         table: "dog".to_string(),
+//!         schema: "dev".to_string(),
 //!         records:json!([{
 //!             "id": 1,
 //!             "name": "Incredible Metal Chair",
@@ -72,21 +73,60 @@
 //! }
 //! ```
 
-use reqwest::Error;
+use futures_util::stream::{self, Stream};
+use json_stream::ArrayDecoder;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 #[macro_use]
 extern crate serde_json;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct HarperConfig {
-    pub url: &'static str,
-    pub username: &'static str,
-    pub password: &'static str,
-    pub schema: &'static str,
-}
+mod batch;
+mod config;
+mod configuration;
+#[cfg(any(feature = "chrono", feature = "time"))]
+pub mod epoch_millis;
+mod error;
+mod job;
+mod json_stream;
+pub mod middleware;
+mod nodes;
+mod operation;
+mod package;
+pub mod sql;
+mod table;
+mod timestamp;
+mod tls;
+mod token;
+mod users;
+#[cfg(feature = "tracing")]
+mod trace;
+
+pub use batch::{Batch, BatchError, BatchReport, Compensation};
+pub use config::{ConfigError, HarperConfig, Timeouts};
+pub use configuration::{
+    ClusteringConfiguration, ClusteringServerConfiguration, ConfigurationChange, CustomFunctionsConfiguration,
+    HttpConfiguration, LoggingConfiguration, NetworkConfiguration, OperationsApiConfiguration, ServerConfiguration,
+    Threads,
+};
+pub use error::{Error, ReplayError};
+pub use job::{Job, JobHandle, JobStatus};
+use middleware::{BasicAuth, DryRun, MetadataCache, Middleware, Next, OperationRequest, RateLimiter};
+pub use middleware::{RateLimit, RateLimits};
+use nodes::NodePool;
+pub use nodes::{NodeSelection, NodeStatus};
+pub use operation::OperationClass;
+pub use package::package_directory;
+pub use table::{Schema, Table};
+pub use timestamp::{ParseTimestampError, Timestamp};
+pub use tls::{ClientIdentity, TlsConfig};
+pub use token::{AuthenticationTokens, TokenClaims};
+pub use users::{ProvisionError, ProvisionReport, Role, User, UserChange, UserSpec};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ResponseData {
@@ -97,20 +137,20 @@ pub struct ResponseData {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SchemaOption {
-    pub schema: &'static str,
+    pub schema: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TableOptions {
-    pub table: &'static str,
-    pub schema: &'static str,
+    pub table: String,
+    pub schema: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CreateTableOptions {
-    pub table: &'static str,
-    pub schema: &'static str,
-    pub hash_attribute: &'static str,
+    pub table: String,
+    pub schema: String,
+    pub hash_attribute: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -122,8 +162,8 @@ pub struct AttributeDropOptions {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct QueryOptions {
-    pub table: &'static str,
-    pub schema: &'static str,
+    pub table: String,
+    pub schema: String,
     pub records: Value,
 }
 
@@ -177,23 +217,23 @@ pub struct FileLoadOptions {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UserAddOptions {
-    pub role: &'static str,
-    pub username: &'static str,
-    pub password: &'static str,
+    pub role: String,
+    pub username: String,
+    pub password: String,
     pub active: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UserAlterOptions {
-    pub role: Option<&'static str>,
-    pub username: &'static str,
-    pub password: Option<&'static str>,
-    pub active: bool,
+    pub role: Option<String>,
+    pub username: String,
+    pub password: Option<String>,
+    pub active: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UserDropOptions {
-    pub username: &'static str,
+    pub username: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -220,7 +260,21 @@ pub struct DropRoleOptions {
 pub struct DeleteFilesBeforeOptions {
     pub schema: &'static str,
     pub table: &'static str,
-    pub date: &'static str,
+    pub date: Timestamp,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeleteRecordsBeforeOptions {
+    pub schema: String,
+    pub table: String,
+    pub date: Timestamp,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeleteAuditLogsBeforeOptions {
+    pub schema: String,
+    pub table: String,
+    pub timestamp: Timestamp,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -251,30 +305,227 @@ pub struct ExportLocalOptions {
     pub search_operation: SearchOperation,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
 pub enum AscDesc {
     Desc,
     Asc,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+    Fatal,
+    Notify,
+    #[serde(other)]
+    Unknown,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LogsOptions {
     pub limit: Option<usize>,
     pub start: Option<usize>,
-    pub from: Option<&'static str>,
-    pub until: Option<&'static str>,
-    pub order: Option<&'static str>,
+    pub level: Option<LogLevel>,
+    pub from: Option<Timestamp>,
+    pub until: Option<Timestamp>,
+    pub order: Option<AscDesc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LogEntry {
+    pub level: LogLevel,
+    pub message: String,
+    pub timestamp: Timestamp,
+    pub thread: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+// Older servers wrap the entries by log transport (`{"file": [...]}`), newer ones
+// return the array directly.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LogsResponse {
+    Entries(Vec<LogEntry>),
+    Transports { file: Vec<LogEntry> },
+}
+
+/// What `read_audit_log` looks for
+#[derive(Debug, Clone)]
+pub enum AuditLogSearch {
+    /// Changes made in a time range; without `until`, up to now
+    Timestamp { from: Timestamp, until: Option<Timestamp> },
+    /// Changes to the records with these hash values, strings or numbers
+    HashValues(Vec<Value>),
+    /// Changes made by these users
+    Usernames(Vec<String>),
+}
+
+#[derive(Debug, Clone)]
+pub struct AuditLogOptions {
+    pub schema: String,
+    pub table: String,
+    /// Every change of the table when unset
+    pub search: Option<AuditLogSearch>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TransactionLogOptions {
+    pub schema: String,
+    pub table: String,
+    pub from: Option<Timestamp>,
+    pub to: Option<Timestamp>,
+    pub limit: Option<usize>,
+}
+
+/// One change to a table, from its audit or transaction log
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TableLogEntry {
+    /// `insert`, `update`, `upsert` or `delete`
+    pub operation: String,
+    /// The user that made the change
+    #[serde(alias = "user_name")]
+    pub user: Option<String>,
+    pub timestamp: Timestamp,
+    /// The hash values of the changed records
+    #[serde(default)]
+    pub hash_values: Vec<Value>,
+    /// The records as written; empty for deletes
+    #[serde(default)]
+    pub records: Vec<Value>,
+    /// The records before the change; only in the audit log, and empty for
+    /// inserts
+    #[serde(default)]
+    pub original_records: Vec<Value>,
+}
+
+// A search by hash value answers with the entries grouped by hash value.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TableLogResponse {
+    Entries(Vec<TableLogEntry>),
+    ByHashValue(HashMap<String, Vec<TableLogEntry>>),
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GetJobOptions {
-    pub id: &'static str,
+    pub id: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct JobsByDateOptions {
-    pub from_date: &'static str,
-    pub to_date: &'static str,
+    pub from_date: Timestamp,
+    pub to_date: Timestamp,
+}
+
+/// The folder of a Custom Functions project a file lives in
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CustomFunctionType {
+    Helpers,
+    Routes,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CustomFunctionOptions {
+    pub project: String,
+    #[serde(rename = "type")]
+    pub function_type: CustomFunctionType,
+    /// The file name without `.js`
+    pub file: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SetCustomFunctionOptions {
+    pub project: String,
+    #[serde(rename = "type")]
+    pub function_type: CustomFunctionType,
+    pub file: String,
+    pub function_content: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CustomFunctionProjectOptions {
+    pub project: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PackageCustomFunctionProjectOptions {
+    pub project: String,
+    pub skip_node_modules: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeployCustomFunctionProjectOptions {
+    pub project: String,
+    /// A base64 encoded tar of the project, from `package_custom_function_project`
+    /// or `package_directory`
+    pub payload: String,
+}
+
+/// Exactly one of `payload` and `package` should be set
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DeployComponentOptions {
+    pub project: String,
+    /// A base64 encoded tar of the component, from `package_component` or
+    /// `package_directory`
+    pub payload: Option<String>,
+    /// An npm package or git reference to install instead, e.g.
+    /// `HarperDB/application-template#semver:v1.0.0`
+    pub package: Option<String>,
+    /// Restart the server afterwards to load the component
+    pub restart: Option<bool>,
+    /// Deploy to every node of the cluster
+    pub replicated: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PackageComponentOptions {
+    pub project: String,
+    pub skip_node_modules: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ComponentFileOptions {
+    pub project: String,
+    /// Path of the file within the component, e.g. `resources.js`
+    pub file: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SetComponentFileOptions {
+    pub project: String,
+    pub file: String,
+    /// The file content
+    pub payload: String,
+    /// `utf8` unless set
+    pub encoding: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DropComponentOptions {
+    pub project: String,
+    /// Drop only this file instead of the whole component
+    pub file: Option<String>,
+}
+
+/// A service `restart_service` can restart on its own
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Service {
+    HttpWorkers,
+    Clustering,
+    CustomFunctions,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RestartServiceOptions {
+    pub service: Service,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -310,25 +561,39 @@ pub struct RemoveNodeOptions {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Attribute {
-    pub attribute: &'static str,
+    pub attribute: String,
 }
 
+/// A table as `describe_table` reports it
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct DescribeTable {
-    pub __createdtime__: usize,
-    pub __updatedtime__: usize,
-    pub hash_attribute: &'static str,
-    pub id: &'static str,
-    pub name: &'static str,
-    pub residence: Option<&'static str>,
-    pub schema: &'static str,
+    #[serde(rename = "__createdtime__", default, skip_serializing_if = "Option::is_none")]
+    pub created_time: Option<Timestamp>,
+    #[serde(rename = "__updatedtime__", default, skip_serializing_if = "Option::is_none")]
+    pub updated_time: Option<Timestamp>,
+    pub hash_attribute: String,
+    pub id: String,
+    pub name: String,
+    /// The nodes the table lives on, when clustering restricts it
+    #[serde(default)]
+    pub residence: Option<Vec<String>>,
+    pub schema: String,
+    #[serde(default)]
     pub record_count: usize,
     pub attributes: Vec<Attribute>,
 }
 
+/// A HarperDB client
+///
+/// Clones are cheap and share connections, node health and middlewares.
+#[derive(Clone)]
 pub struct Harper {
-    config: HarperConfig,
+    config: Arc<HarperConfig>,
     client: reqwest::Client,
+    nodes: Arc<NodePool>,
+    middlewares: Arc<Vec<Arc<dyn Middleware>>>,
+    metadata: Option<Arc<MetadataCache>>,
+    timeout: Option<Duration>,
 }
 impl Harper {
     /// Create a HaperDB client
@@ -341,19 +606,418 @@ impl Harper {
     ///
     /// ```
     /// let config: HarperConfig = HarperConfig {
-    ///     url: "http://0.0.0.0:9925/",
-    ///     username: "HDB_ADMIN",
-    ///     password: "password",
-    ///     schema: "shop",
+    ///     url: "http://0.0.0.0:9925/".to_string(),
+    ///     username: "HDB_ADMIN".to_string(),
+    ///     password: "password".to_string(),
+    ///     schema: "shop".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let harper_client = new(config);
+    /// ```
+    ///
+    /// Failing over across cluster nodes:
+    ///
+    /// ```
+    /// let config: HarperConfig = HarperConfig {
+    ///     url: "http://node1:9925/".to_string(),
+    ///     username: "HDB_ADMIN".to_string(),
+    ///     password: "password".to_string(),
+    ///     nodes: vec!["http://node2:9925/".to_string(), "http://node3:9925/".to_string()],
+    ///     node_selection: NodeSelection::RoundRobin,
+    ///     ..Default::default()
     /// };
     ///
     /// let harper_client = new(config);
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics when a node URL or `harper_config.tls` is invalid; use `try_new` to
+    /// handle that as an error.
     pub fn new(harper_config: HarperConfig) -> Self {
+        Harper::try_new(harper_config).expect("invalid HarperDB client configuration")
+    }
+
+    /// Create a HarperDB client, failing on an invalid node URL or TLS configuration
+    ///
+    /// # Arguments
+    ///
+    /// * `harper_config`  (required) - HarperConfig
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let config: HarperConfig = HarperConfig {
+    ///     url: "https://harperdb.internal:9926/".to_string(),
+    ///     username: "HDB_ADMIN".to_string(),
+    ///     password: "password".to_string(),
+    ///     tls: TlsConfig {
+    ///         root_certificates: vec![std::fs::read("internal-ca.pem")?],
+    ///         identity: Some(ClientIdentity::Pkcs12 {
+    ///             der: std::fs::read("client.p12")?,
+    ///             password: "secret".to_string(),
+    ///         }),
+    ///         ..Default::default()
+    ///     },
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let harper_client = Harper::try_new(config)?;
+    /// ```
+    pub fn try_new(harper_config: HarperConfig) -> Result<Self, Error> {
+        let harper_config = harper_config.validated()?;
+        let client = tls::client_builder(&harper_config.tls)?
+            .connect_timeout(harper_config.timeouts.connect)
+            .build()?;
+        let urls = std::iter::once(&harper_config.url)
+            .chain(harper_config.nodes.iter())
+            .cloned()
+            .collect();
+        let nodes = Arc::new(NodePool::new(urls, harper_config.node_selection));
+        let mut middlewares: Vec<Arc<dyn Middleware>> = vec![Arc::new(BasicAuth::new(
+            &harper_config.username,
+            &harper_config.password,
+        ))];
+        let metadata = harper_config.metadata_ttl.map(|ttl| Arc::new(MetadataCache::new(ttl)));
+        if let Some(metadata) = &metadata {
+            middlewares.push(metadata.clone());
+        }
+        if !harper_config.limits.is_unlimited() {
+            middlewares.push(Arc::new(RateLimiter::new(&harper_config.limits)));
+        }
+
+        Ok(Harper {
+            config: Arc::new(harper_config),
+            client,
+            nodes,
+            middlewares: Arc::new(middlewares),
+            metadata,
+            timeout: None,
+        })
+    }
+
+    /// Register a middleware
+    ///
+    /// Middlewares run in registration order, after the built-in `BasicAuth`,
+    /// `MetadataCache` and `RateLimiter`.
+    ///
+    /// # Arguments
+    ///
+    /// * `middleware`  (required) - impl Middleware
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let metrics = middleware::Metrics::new();
+    /// let harper_client = Harper::new(config)
+    ///     .with_middleware(middleware::Retry::new(3))
+    ///     .with_middleware(metrics.clone());
+    /// ```
+    pub fn with_middleware<M: Middleware + 'static>(mut self, middleware: M) -> Self {
+        Arc::make_mut(&mut self.middlewares).push(Arc::new(middleware));
+        self
+    }
+
+    /// Override the request timeout
+    ///
+    /// Returns a client that shares everything with this one except that its
+    /// operations use `timeout` instead of the configured `Timeouts`.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout`  (required) - Duration
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let result = harper_client
+    ///     .with_timeout(Duration::from_secs(3600))
+    ///     .csv_url_load(csv_url_load_option)
+    ///     .await?;
+    /// ```
+    pub fn with_timeout(&self, timeout: Duration) -> Self {
         Harper {
-            config: harper_config,
-            client: reqwest::Client::new(),
+            timeout: Some(timeout),
+            ..self.clone()
+        }
+    }
+
+    /// Dry Run
+    ///
+    /// Returns a client that captures operations into `dry_run` instead of
+    /// sending them. It shares everything else with this one, except the
+    /// metadata cache: describes are never served from it or stored in it, so
+    /// placeholder answers cannot reach this client.
+    ///
+    /// # Arguments
+    ///
+    /// * `dry_run`  (required) - DryRun
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let recorder = middleware::DryRun::to_file("migration.ndjson")?;
+    /// let dry_client = harper_client.dry_run(recorder.clone());
+    /// dry_client.drop_attribute(attribute_drop_option).await?;
+    /// println!("{:#?}", recorder.operations());
+    /// ```
+    ///
+    pub fn dry_run(&self, dry_run: DryRun) -> Self {
+        let middlewares = self
+            .middlewares
+            .iter()
+            .filter(|middleware| match &self.metadata {
+                Some(metadata) => !std::ptr::addr_eq(Arc::as_ptr(middleware), Arc::as_ptr(metadata)),
+                None => true,
+            })
+            .cloned()
+            .collect();
+        let dry_client = Harper {
+            middlewares: Arc::new(middlewares),
+            metadata: None,
+            ..self.clone()
+        };
+        dry_client.with_middleware(dry_run)
+    }
+
+    /// Replay
+    ///
+    /// Sends recorded operations in order, stopping at the first one that fails
+    /// to send or is answered with an error status. Returns the response bodies.
+    ///
+    /// # Arguments
+    ///
+    /// * `operations`  (required) - &[Value], e.g. from `DryRun::load`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let operations = middleware::DryRun::load("migration.ndjson")?;
+    /// let responses = harper_client.replay(&operations).await?;
+    /// ```
+    ///
+    pub async fn replay(&self, operations: &[Value]) -> Result<Vec<Value>, ReplayError> {
+        let mut responses = Vec::with_capacity(operations.len());
+        for (index, operation) in operations.iter().enumerate() {
+            let res = match self.request(operation).await {
+                Ok(res) => Error::check(res).await,
+                Err(error) => Err(error),
+            };
+            let body = match res {
+                Ok(res) => res.json().await.map_err(Error::from),
+                Err(error) => Err(error),
+            };
+            match body {
+                Ok(body) => responses.push(body),
+                Err(error) => return Err(ReplayError { index, error }),
+            }
+        }
+        Ok(responses)
+    }
+
+    /// Refresh Metadata
+    ///
+    /// Empties the metadata cache enabled by `HarperConfig.metadata_ttl`, so the
+    /// next `describe_all`, `describe_schema` or `describe_table` asks the server.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// harper_client.refresh_metadata();
+    /// let result = harper_client.describe_table(table_option).await?;
+    /// ```
+    ///
+    pub fn refresh_metadata(&self) {
+        if let Some(metadata) = &self.metadata {
+            metadata.invalidate_all();
+        }
+    }
+
+    /// Node Status
+    ///
+    /// Reports which configured nodes the client currently considers reachable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// for node in harper_client.node_status() {
+    ///     println!("{} healthy: {}", node.url, node.healthy);
+    /// }
+    /// ```
+    ///
+    pub fn node_status(&self) -> Vec<NodeStatus> {
+        self.nodes.status()
+    }
+
+    /// Schema
+    ///
+    /// Returns a handle for the schema `name`, whose tables are reached with
+    /// `Schema::table`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name`  (required) - impl Into<String>
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let dog = harper_client.schema("dev").table("dog");
+    /// let result = dog.insert(json!([{ "id": 1, "name": "Penny" }])).await?;
+    /// ```
+    ///
+    pub fn schema(&self, name: impl Into<String>) -> Schema {
+        Schema::new(self.clone(), name.into())
+    }
+
+    /// Table
+    ///
+    /// Returns a handle for the table `name` of the default schema,
+    /// `HarperConfig.schema`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name`  (required) - impl Into<String>
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let dog = harper_client.table("dog");
+    /// let result = dog.get(&[1], &["*"]).await?;
+    /// ```
+    ///
+    pub fn table(&self, name: impl Into<String>) -> Table {
+        Table::new(self.clone(), self.config.schema.clone(), name.into())
+    }
+
+    /// Batch
+    ///
+    /// Starts a `Batch` of insert, update and delete operations that are undone
+    /// with compensating operations when one of them fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let result = harper_client
+    ///     .batch()
+    ///     .insert(QueryOptions {
+    ///         table: "order".to_string(),
+    ///         schema: "shop".to_string(),
+    ///         records: json!([{ "id": "o-1", "item": "i-7", "quantity": 2 }]),
+    ///     })
+    ///     .update(QueryOptions {
+    ///         table: "inventory".to_string(),
+    ///         schema: "shop".to_string(),
+    ///         records: json!([{ "id": "i-7", "stock": 8 }]),
+    ///     })
+    ///     .run()
+    ///     .await;
+    ///
+    /// if let Err(error) = result {
+    ///     for compensation in &error.rolled_back {
+    ///         println!("undid step {}: {}", compensation.step, compensation.operation);
+    ///     }
+    /// }
+    /// ```
+    ///
+    pub fn batch(&self) -> Batch<'_> {
+        Batch::new(self)
+    }
+
+    /// Send one operation, failing over to the next node when a node cannot be
+    /// reached. Only connection failures fail over: the request never reached the
+    /// server, so retrying it elsewhere is safe for writes too.
+    async fn request<T: Serialize + ?Sized>(&self, operation: &T) -> Result<reqwest::Response, Error> {
+        let operation = serde_json::to_value(operation)?;
+
+        #[cfg(feature = "tracing")]
+        {
+            use tracing::Instrument;
+
+            let span = trace::operation_span(&operation);
+            let started = std::time::Instant::now();
+            let res = self.send(operation).instrument(span.clone()).await;
+            trace::record_result(&span, &res, started.elapsed());
+            res
         }
+
+        #[cfg(not(feature = "tracing"))]
+        self.send(operation).await
+    }
+
+    async fn send(&self, operation: Value) -> Result<reqwest::Response, Error> {
+        let mut last_error = None;
+
+        for index in self.nodes.candidates() {
+            #[cfg(feature = "tracing")]
+            trace::record_node(self.nodes.url(index));
+
+            let request = OperationRequest {
+                operation: operation.clone(),
+                url: self.nodes.url(index).to_string(),
+                headers: Default::default(),
+                timeout: self
+                    .timeout
+                    .unwrap_or_else(|| self.config.timeouts.for_operation(&operation)),
+            };
+            let res = Next::new(&self.client, &self.middlewares).run(request).await;
+
+            match res {
+                Ok(res) => {
+                    self.nodes.mark_healthy(index);
+                    return Ok(res);
+                }
+                Err(error) if error.is_connect() => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(node = self.nodes.url(index), "node unreachable, failing over");
+
+                    if self.nodes.mark_unhealthy(index) {
+                        self.spawn_health_check(index);
+                    }
+                    last_error = Some(error);
+                }
+                Err(error) => return Err(error),
+            }
+        }
+
+        Err(last_error.expect("the node pool always holds at least one node"))
+    }
+
+    /// Probe an unreachable node with a cheap `user_info` until it answers, then
+    /// put it back into rotation. The probe stops when the client is dropped.
+    fn spawn_health_check(&self, index: usize) {
+        let nodes: Weak<NodePool> = Arc::downgrade(&self.nodes);
+        let client = self.client.clone();
+        let username = self.config.username.clone();
+        let password = self.config.password.clone();
+        let interval = self.config.health_check_interval;
+        let timeout = self.config.timeouts.lookup;
+
+        tokio::spawn(async move {
+            loop {
+                tokio::time::delay_for(interval).await;
+                let nodes = match nodes.upgrade() {
+                    Some(nodes) => nodes,
+                    None => return,
+                };
+                if nodes.is_healthy(index) {
+                    return;
+                }
+                let res = client
+                    .post(nodes.url(index))
+                    .basic_auth(&username, Some(&password))
+                    .timeout(timeout)
+                    .json(&json!({ "operation": "user_info" }))
+                    .send()
+                    .await;
+                if let Ok(res) = res {
+                    if !res.status().is_server_error() {
+                        nodes.mark_healthy(index);
+                        return;
+                    }
+                }
+            }
+        });
     }
 
     /// Create Schema:
@@ -366,7 +1030,7 @@ impl Harper {
     /// 
     /// ```
     /// let schema_option: SchemaOption = SchemaOption {
-    ///     schema: "newschema",
+    ///     schema: "newschema".to_string(),
     /// };
     /// let result = harper_client.create_schema(schema_option).await?;
     /// ```
@@ -376,13 +1040,7 @@ impl Harper {
         map.insert("operation", "create_schema");
         map.insert("schema", &options.schema);
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -397,7 +1055,7 @@ impl Harper {
     /// 
     /// ```
     /// let schema_option: SchemaOption = SchemaOption {
-    ///     schema: "newschema",
+    ///     schema: "newschema".to_string(),
     /// };
     /// let result = harper_client.drop_schema(schema_option).await?;
     /// ```
@@ -407,13 +1065,7 @@ impl Harper {
         map.insert("operation", "drop_schema");
         map.insert("schema", &options.schema);
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -428,7 +1080,7 @@ impl Harper {
     /// 
     /// ```
     /// let schema_option: SchemaOption = SchemaOption {
-    ///     schema: "newschema",
+    ///     schema: "newschema".to_string(),
     /// };
     /// let result = harper_client.describe_schema(schema_option).await?;
     /// ```
@@ -438,13 +1090,7 @@ impl Harper {
         map.insert("operation", "describe_schema");
         map.insert("schema", &options.schema);
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -461,13 +1107,7 @@ impl Harper {
         let mut map = HashMap::new();
         map.insert("operation", "describe_all");
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -482,9 +1122,9 @@ impl Harper {
     /// 
     /// ```
     /// let create_table_option: CreateTableOptions = CreateTableOptions {
-    ///     hash_attribute: "id",
-    ///     table: "test_table",
-    ///     schema: "describe_schema_test",
+    ///     hash_attribute: "id".to_string(),
+    ///     table: "test_table".to_string(),
+    ///     schema: "describe_schema_test".to_string(),
     /// };
     /// let result = harper_client.create_table(create_table_option).await?;
     /// ```
@@ -499,13 +1139,7 @@ impl Harper {
         map.insert("table", &options.table);
         map.insert("schema", &options.schema);
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -520,8 +1154,8 @@ impl Harper {
     /// 
     /// ```
     /// let table_option: TableOptions = TableOptions {
-    ///     table: "tablename",
-    ///     schema: "newschema",
+    ///     table: "tablename".to_string(),
+    ///     schema: "newschema".to_string(),
     /// };
     /// let result = harper_client.Describe Table(table_option).await?;
     /// ```
@@ -532,13 +1166,7 @@ impl Harper {
         map.insert("table", &options.table);
         map.insert("schema", &options.schema);
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
         // let gist: DescribeTable = res.json().await?;
         // println!("{:#?}", gist);
         // let result = res.json().await?;
@@ -557,7 +1185,7 @@ impl Harper {
     /// 
     /// ```
     /// let schema_option: SchemaOption = SchemaOption {
-    ///     schema: "newschema",
+    ///     schema: "newschema".to_string(),
     /// };
     /// let result = harper_client.create_schema(schema_option).await?;
     /// ```
@@ -568,13 +1196,7 @@ impl Harper {
         map.insert("table", &options.table);
         map.insert("schema", &options.schema);
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -603,13 +1225,7 @@ impl Harper {
         map.insert("schema", &options.schema);
         map.insert("attribute", &options.attribute);
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -626,24 +1242,83 @@ impl Harper {
     /// let result = harper_client.query("SELECT * FROM testing.crud_table_test limit 2",).await?;
     /// ```
     /// 
-    pub async fn query(&self, sql_query: &'static str, ) -> Result<reqwest::Response, Error> {
+    pub async fn query(&self, sql_query: &str) -> Result<reqwest::Response, Error> {
 
         let map = json!({
             "operation": "sql",
             "sql": &sql_query,
         });
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
 
+    /// Query Stream
+    ///
+    /// Runs a SQL query and yields its rows while the response is still
+    /// arriving. The rows are decoded one at a time, so memory use is bounded by
+    /// the largest row rather than the size of the result. The stream ends after
+    /// yielding an error.
+    ///
+    /// The timeout covers reading the whole result, so it is `Timeouts.bulk`
+    /// rather than `Timeouts.request`, unless `with_timeout` overrides it.
+    ///
+    /// # Arguments
+    ///
+    /// * `sql`  (required) - &str
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut dogs = Box::pin(harper_client.query_stream::<DogRecord>("SELECT * FROM dev.dog"));
+    /// while let Some(dog) = dogs.next().await {
+    ///     println!("{:#?}", dog?);
+    /// }
+    /// ```
+    ///
+    pub fn query_stream<'a, T: DeserializeOwned + 'a>(
+        &'a self,
+        sql_query: &'a str,
+    ) -> impl Stream<Item = Result<T, Error>> + 'a {
+        enum State {
+            Start,
+            Body(Box<reqwest::Response>, ArrayDecoder, VecDeque<Vec<u8>>),
+            Done,
+        }
+
+        stream::unfold(State::Start, move |state| async move {
+            let (mut res, mut decoder, mut rows) = match state {
+                State::Start => match self.with_timeout(self.timeout.unwrap_or(self.config.timeouts.bulk)).query(sql_query).await {
+                    Ok(res) => match Error::check(res).await {
+                        Ok(res) => (Box::new(res), ArrayDecoder::new(), VecDeque::new()),
+                        Err(error) => return Some((Err(error), State::Done)),
+                    },
+                    Err(error) => return Some((Err(error), State::Done)),
+                },
+                State::Body(res, decoder, rows) => (res, decoder, rows),
+                State::Done => return None,
+            };
+
+            loop {
+                if let Some(row) = rows.pop_front() {
+                    return Some(match serde_json::from_slice(&row) {
+                        Ok(row) => (Ok(row), State::Body(res, decoder, rows)),
+                        Err(error) => (Err(error.into()), State::Done),
+                    });
+                }
+                match res.chunk().await {
+                    Ok(Some(chunk)) => match decoder.push(&chunk) {
+                        Ok(complete) => rows.extend(complete),
+                        Err(error) => return Some((Err(error), State::Done)),
+                    },
+                    Ok(None) => return decoder.finish().err().map(|error| (Err(error), State::Done)),
+                    Err(error) => return Some((Err(error.into()), State::Done)),
+                }
+            }
+        })
+    }
+
     /// Insert Records
     /// 
     /// # Arguments
@@ -654,8 +1329,8 @@ impl Harper {
     /// 
     /// ```
     /// let insert_option: QueryOptions = QueryOptions {
-    ///     table: "crud_table_test",
-    ///     schema: "testing",
+    ///     table: "crud_table_test".to_string(),
+    ///     schema: "testing".to_string(),
     ///     records:json!([{
     ///         "id": "record1234",
     ///         "name": "Incredible Metal Chair",
@@ -679,13 +1354,7 @@ impl Harper {
             "records": &options.records
         });
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -700,8 +1369,8 @@ impl Harper {
     /// 
     /// ```
     /// let update_option: QueryOptions = QueryOptions {
-    ///     table: "crud_table_test",
-    ///     schema: "testing",
+    ///     table: "crud_table_test".to_string(),
+    ///     schema: "testing".to_string(),
     ///     records:json!([{            
     ///         "id": "record1234",           
     ///         "color": "red",            
@@ -719,13 +1388,7 @@ impl Harper {
             "records": &options.records
         });
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -755,13 +1418,7 @@ impl Harper {
             "hash_values": &options.hash_values
         });
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -797,13 +1454,7 @@ impl Harper {
             "get_attributes": &options.get_attributes
         });
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -839,13 +1490,7 @@ impl Harper {
             "get_attributes": &options.get_attributes
         });
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -877,13 +1522,7 @@ impl Harper {
         map.insert("action", &options.action);
         map.insert("data", &options.data);
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -915,13 +1554,7 @@ impl Harper {
         map.insert("action", &options.action);
         map.insert("csv_url", &options.csv_url);
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -953,13 +1586,7 @@ impl Harper {
         map.insert("action", &options.action);
         map.insert("file_path", &options.file_path);
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -967,48 +1594,41 @@ impl Harper {
 
     /// List Users:
     /// 
+    /// Roles reported as ids are looked up with `list_roles`.
+    /// 
     /// # Examples
     /// 
     /// ```
-    /// let result = harper_client.list_users().await?;
+    /// let users: Vec<User> = harper_client.list_users().await?;
     /// ```
     /// 
-    pub async fn list_users(&self) -> Result<reqwest::Response, Error> {
+    pub async fn list_users(&self) -> Result<Vec<User>, Error> {
         let mut map = HashMap::new();
         map.insert("operation", "list_users");
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = Error::check(self.request(&map).await?).await?;
 
-        Ok(res)
+        users::expand_roles(self, res.json().await?).await
     }
 
     /// User Info
     /// 
+    /// The user the client signs in as.
+    /// 
     /// # Examples
     /// 
     /// ```
-    /// let result = harper_client.user_info().await?;
+    /// let user: User = harper_client.user_info().await?;
     /// ```
     ///  
-    pub async fn user_info(&self) -> Result<reqwest::Response, Error> {
+    pub async fn user_info(&self) -> Result<User, Error> {
         let mut map = HashMap::new();
         map.insert("operation", "user_info");
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = Error::check(self.request(&map).await?).await?;
+        let mut users = users::expand_roles(self, vec![res.json().await?]).await?;
 
-        Ok(res)
+        Ok(users.remove(0))
     }
 
     /// Add User
@@ -1021,9 +1641,9 @@ impl Harper {
     /// 
     /// ```
     /// let user_option: UserAddOptions = UserAddOptions {
-    ///     role: "c0a90733-1fc3-48df-a16b-d7c3011b63b2",
-    ///     username: "john",
-    ///     password: "secret",
+    ///     role: "c0a90733-1fc3-48df-a16b-d7c3011b63b2".to_string(),
+    ///     username: "john".to_string(),
+    ///     password: "secret".to_string(),
     ///     active: true
     /// };
     /// let result = harper_client.add_user(user_option).await?;
@@ -1039,19 +1659,15 @@ impl Harper {
             "active": &options.active
         });
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
 
     /// Alter User
     /// 
+    /// Only the fields that are set are changed.
+    /// 
     /// # Arguments
     /// 
     /// * `options`  (required) - UserAlterOptions
@@ -1060,31 +1676,30 @@ impl Harper {
     /// 
     /// ```
     /// let user_option: UserAlterOptions = UserAlterOptions {
-    ///     role: Some("c0a90733-1fc3-48df-a16b-d7c3011b63b2"),
-    ///     username: "john",
-    ///     password: Some("secret2"),
-    ///     active: true
+    ///     role: Some("c0a90733-1fc3-48df-a16b-d7c3011b63b2".to_string()),
+    ///     username: "john".to_string(),
+    ///     password: None,
+    ///     active: Some(true)
     /// };
     /// let result = harper_client.alter_user(user_option).await?;
     /// ```
     /// 
     pub async fn alter_user(&self, options: UserAlterOptions) -> Result<reqwest::Response, Error> {
-        let map = json!({
+        let mut map = json!({
             "operation": "alter_user",
-            "role": &options.role,
             "username": &options.username,
-            "password": &options.password,
-            // "password": &options.password,
-            "active": &options.active
         });
+        if let Some(role) = &options.role {
+            map["role"] = json!(role);
+        }
+        if let Some(password) = &options.password {
+            map["password"] = json!(password);
+        }
+        if let Some(active) = options.active {
+            map["active"] = json!(active);
+        }
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -1099,7 +1714,7 @@ impl Harper {
     /// 
     /// ```
     /// let user_option: UserDropOptions = UserDropOptions {
-    ///     username: "john",
+    ///     username: "john".to_string(),
     /// };
     /// let result = harper_client.drop_user(user_option).await?;
     /// ```
@@ -1109,18 +1724,43 @@ impl Harper {
         map.insert("operation", "drop_user");
         map.insert("username", &options.username);
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
 
-    /// List Roles
+    /// Provision Users
+    /// 
+    /// Makes the users match `desired`: creates the missing ones, updates the
+    /// role or active flag of changed ones, and deactivates active users that
+    /// are not listed, except the one the client signs in as. With `dry_run`,
+    /// only reports what it would change.
+    /// 
+    /// Roles, passwords of new users and duplicate usernames are checked before
+    /// anything is changed. Changes are applied in the order of the report and
+    /// stop at the first failure, leaving the earlier ones in place; the
+    /// `ProvisionError` lists them.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `desired`  (required) - &[UserSpec], e.g. from `UserSpec::load`
+    /// * `dry_run`  (required) - bool
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// let desired = UserSpec::load("users.toml")?;
+    /// let report: ProvisionReport = harper_client.provision_users(&desired, true).await?;
+    /// for change in &report.changes {
+    ///     println!("{}", change);
+    /// }
+    /// ```
+    /// 
+    pub async fn provision_users(&self, desired: &[UserSpec], dry_run: bool) -> Result<ProvisionReport, ProvisionError> {
+        users::provision(self, desired, dry_run).await
+    }
+
+    /// List Roles
     /// 
     /// # Examples
     /// 
@@ -1132,13 +1772,7 @@ impl Harper {
         let mut map = HashMap::new();
         map.insert("operation", "list_roles");
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -1191,13 +1825,7 @@ impl Harper {
 
         map["permission"]["super_user"]=Value::Bool(options.super_user);
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -1250,13 +1878,7 @@ impl Harper {
 
         map["permission"]["super_user"]=Value::Bool(options.super_user);
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -1281,13 +1903,7 @@ impl Harper {
         map.insert("operation", "drop_role");
         map.insert("id", &options.id);
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -1314,19 +1930,17 @@ impl Harper {
             "attributes": &options.attributes            
         });
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
 
     /// Delete Files Before
     /// 
+    /// Starts a job deleting the records of a table last updated before
+    /// `date`. The `chrono` and `time` features let `date` be given as a
+    /// `DateTime`, `NaiveDate`, `OffsetDateTime` or `Date` with `.into()`.
+    /// 
     /// # Arguments
     /// 
     /// * `options`  (required) - DeleteFilesBeforeOptions
@@ -1335,29 +1949,89 @@ impl Harper {
     /// 
     /// ```
     /// let option: DeleteFilesBeforeOptions = DeleteFilesBeforeOptions {
-    ///     date: "2018-07-10",
+    ///     date: "2018-07-10".parse()?,
     ///     schema: "dev",
     ///     table: "breed",
     /// };
-    /// let result = harper_client.delete_files_before(option).await?;
+    /// let job: JobHandle = harper_client.delete_files_before(option).await?;
     /// ```
     ///
-    pub async fn delete_files_before(&self, options: DeleteFilesBeforeOptions) -> Result<reqwest::Response, Error> {
-        let mut map = HashMap::new();
-        map.insert("operation", "delete_files_before");
-        map.insert("table", &options.table);
-        map.insert("schema", &options.schema);
-        map.insert("date", &options.date);
+    pub async fn delete_files_before(&self, options: DeleteFilesBeforeOptions) -> Result<JobHandle, Error> {
+        let map = json!({
+            "operation": "delete_files_before",
+            "schema": &options.schema,
+            "table": &options.table,
+            "date": &options.date,
+        });
+        let res = self.request(&map).await?;
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        JobHandle::from_response(self, res).await
+    }
 
-        Ok(res)
+    /// Delete Records Before
+    /// 
+    /// The newer name of `delete_files_before`: starts a job deleting the
+    /// records of a table last updated before `date`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `options`  (required) - DeleteRecordsBeforeOptions
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// let option: DeleteRecordsBeforeOptions = DeleteRecordsBeforeOptions {
+    ///     date: Utc::now().checked_sub_signed(Duration::days(30)).unwrap().into(),
+    ///     schema: "dev".to_string(),
+    ///     table: "breed".to_string(),
+    /// };
+    /// let job: JobHandle = harper_client.delete_records_before(option).await?;
+    /// let job: Job = job.wait(std::time::Duration::from_secs(1), std::time::Duration::from_secs(600)).await?;
+    /// ```
+    ///
+    pub async fn delete_records_before(&self, options: DeleteRecordsBeforeOptions) -> Result<JobHandle, Error> {
+        let map = json!({
+            "operation": "delete_records_before",
+            "schema": &options.schema,
+            "table": &options.table,
+            "date": &options.date,
+        });
+        let res = self.request(&map).await?;
+
+        JobHandle::from_response(self, res).await
+    }
+
+    /// Delete Audit Logs Before
+    /// 
+    /// Starts a job deleting the audit log entries of a table older than
+    /// `timestamp`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `options`  (required) - DeleteAuditLogsBeforeOptions
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// let option: DeleteAuditLogsBeforeOptions = DeleteAuditLogsBeforeOptions {
+    ///     timestamp: "2022-08-15T17:47:00Z".parse()?,
+    ///     schema: "dev".to_string(),
+    ///     table: "dog".to_string(),
+    /// };
+    /// let job: JobHandle = harper_client.delete_audit_logs_before(option).await?;
+    /// ```
+    ///
+    pub async fn delete_audit_logs_before(&self, options: DeleteAuditLogsBeforeOptions) -> Result<JobHandle, Error> {
+        // Unlike the other retention operations, this one takes epoch milliseconds.
+        let map = json!({
+            "operation": "delete_audit_logs_before",
+            "schema": &options.schema,
+            "table": &options.table,
+            "timestamp": options.timestamp.as_millis(),
+        });
+        let res = self.request(&map).await?;
+
+        JobHandle::from_response(self, res).await
     }
 
     /// Export To S3
@@ -1392,13 +2066,7 @@ impl Harper {
             "search_operation":&options.search_operation,                        
         });
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -1431,13 +2099,7 @@ impl Harper {
             "search_operation":&options.search_operation,                        
         });
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -1454,31 +2116,229 @@ impl Harper {
     /// let read_logs_option: LogsOptions = LogsOptions {
     ///     limit : Some(2),
     ///     start : Some(0),
+    ///     level : Some(LogLevel::Error),
     ///     from : None,
     ///     until : None,
-    ///     order : None,    
+    ///     order : Some(AscDesc::Desc),
     /// };
-    /// let result = harper_client.read_logs(read_logs_option).await?;
+    /// let entries: Vec<LogEntry> = harper_client.read_logs(read_logs_option).await?;
     /// ```
     /// 
-    pub async fn read_logs(&self, options: LogsOptions) -> Result<reqwest::Response, Error> {
+    pub async fn read_logs(&self, options: LogsOptions) -> Result<Vec<LogEntry>, Error> {
         let map = json!({
             "operation": "read_log",
             "limit": &options.limit,
             "start":&options.start,
+            "level":&options.level,
             "from":&options.from,
             "until":&options.until,
             "order":&options.order,
         });
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = Error::check(self.request(&map).await?).await?;
 
-        Ok(res)
+        let entries = match res.json().await? {
+            LogsResponse::Entries(entries) => entries,
+            LogsResponse::Transports { file } => file,
+        };
+
+        Ok(entries)
+    }
+
+    /// Follow Log
+    ///
+    /// Returns a stream that polls `read_log` every `poll_interval` and yields the
+    /// entries logged after the newest one seen so far, like `tail -f`. Following
+    /// starts at `options.from`, or at the current time when it is unset; `order`,
+    /// `start` and `until` are ignored. Entries sharing the millisecond of the
+    /// newest one seen are told apart by message, so each is yielded once; polls
+    /// ask for `limit` entries (HarperDB's default of 1000 when unset) on top of
+    /// those, so a busy millisecond cannot fill every page. The stream ends
+    /// after yielding an error.
+    ///
+    /// # Arguments
+    ///
+    /// * `options`  (required) - LogsOptions
+    /// * `poll_interval`  (required) - Duration
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let follow_logs_option: LogsOptions = LogsOptions {
+    ///     limit : Some(100),
+    ///     start : None,
+    ///     level : Some(LogLevel::Warn),
+    ///     from : None,
+    ///     until : None,
+    ///     order : None,
+    /// };
+    /// let mut logs = Box::pin(harper_client.follow_logs(follow_logs_option, Duration::from_secs(1)));
+    /// while let Some(entry) = logs.next().await {
+    ///     println!("{:#?}", entry?);
+    /// }
+    /// ```
+    ///
+    pub fn follow_logs(
+        &self,
+        options: LogsOptions,
+        poll_interval: Duration,
+    ) -> impl Stream<Item = Result<LogEntry, Error>> + '_ {
+        const DEFAULT_LIMIT: usize = 1000;
+        let last_seen = options.from.unwrap_or_else(Timestamp::now);
+        let state = (VecDeque::new(), last_seen, Vec::new(), false, true);
+
+        // `boundary` holds the messages already yielded at `last_seen`: the next
+        // poll starts at that millisecond, so they come back along with entries
+        // logged in the same millisecond after the previous poll.
+        stream::unfold(state, move |(mut pending, mut last_seen, mut boundary, failed, first_poll)| {
+            let options = options.clone();
+            async move {
+                if failed {
+                    return None;
+                }
+                let mut first_poll = first_poll;
+                while pending.is_empty() {
+                    if !first_poll {
+                        tokio::time::delay_for(poll_interval).await;
+                    }
+                    first_poll = false;
+
+                    let poll_option = LogsOptions {
+                        limit: Some(options.limit.unwrap_or(DEFAULT_LIMIT) + boundary.len()),
+                        start: None,
+                        from: Some(last_seen),
+                        until: None,
+                        order: Some(AscDesc::Asc),
+                        ..options.clone()
+                    };
+                    match self.read_logs(poll_option).await {
+                        Ok(entries) => {
+                            let mut seen: Vec<String> = boundary.clone();
+                            let mut entries: Vec<LogEntry> = entries
+                                .into_iter()
+                                .filter(|entry| {
+                                    if entry.timestamp != last_seen {
+                                        return entry.timestamp > last_seen;
+                                    }
+                                    match seen.iter().position(|message| *message == entry.message) {
+                                        Some(index) => {
+                                            seen.swap_remove(index);
+                                            false
+                                        }
+                                        None => true,
+                                    }
+                                })
+                                .collect();
+                            entries.sort_by_key(|entry| entry.timestamp);
+                            if let Some(newest) = entries.last() {
+                                if newest.timestamp != last_seen {
+                                    last_seen = newest.timestamp;
+                                    boundary.clear();
+                                }
+                                let at_boundary = entries.iter().filter(|entry| entry.timestamp == last_seen);
+                                boundary.extend(at_boundary.map(|entry| entry.message.clone()));
+                            }
+                            pending.extend(entries);
+                        }
+                        Err(error) => return Some((Err(error), (pending, last_seen, boundary, true, false))),
+                    }
+                }
+                let entry = pending.pop_front()?;
+                Some((Ok(entry), (pending, last_seen, boundary, false, false)))
+            }
+        })
+    }
+
+    /// Read Audit Log
+    ///
+    /// Reads the changes recorded for a table in its audit log, which must be
+    /// enabled on the server (`logging.auditLog`). Entries are ordered by
+    /// timestamp.
+    ///
+    /// # Arguments
+    ///
+    /// * `options`  (required) - AuditLogOptions
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let audit_log_option: AuditLogOptions = AuditLogOptions {
+    ///     schema: "dev".to_string(),
+    ///     table: "dog".to_string(),
+    ///     search: Some(AuditLogSearch::HashValues(vec![json!(318)])),
+    /// };
+    /// let entries: Vec<TableLogEntry> = harper_client.read_audit_log(audit_log_option).await?;
+    /// ```
+    ///
+    pub async fn read_audit_log(&self, options: AuditLogOptions) -> Result<Vec<TableLogEntry>, Error> {
+        let mut map = json!({
+            "operation": "read_audit_log",
+            "schema": &options.schema,
+            "table": &options.table,
+        });
+        let search = match &options.search {
+            Some(AuditLogSearch::Timestamp { from, until }) => {
+                let mut range = vec![from.as_millis()];
+                range.extend(until.map(|until| until.as_millis()));
+                Some(("timestamp", json!(range)))
+            }
+            Some(AuditLogSearch::HashValues(hash_values)) => Some(("hash_value", json!(hash_values))),
+            Some(AuditLogSearch::Usernames(usernames)) => Some(("username", json!(usernames))),
+            None => None,
+        };
+        if let Some((search_type, search_values)) = search {
+            map["search_type"] = json!(search_type);
+            map["search_values"] = search_values;
+        }
+        let res = Error::check(self.request(&map).await?).await?;
+
+        let mut entries = match res.json().await? {
+            TableLogResponse::Entries(entries) => entries,
+            TableLogResponse::ByHashValue(groups) => groups.into_values().flatten().collect(),
+        };
+        entries.sort_by_key(|entry| entry.timestamp);
+
+        Ok(entries)
+    }
+
+    /// Read Transaction Log
+    ///
+    /// Reads the changes recorded for a table in its transaction log, oldest
+    /// first.
+    ///
+    /// # Arguments
+    ///
+    /// * `options`  (required) - TransactionLogOptions
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let transaction_log_option: TransactionLogOptions = TransactionLogOptions {
+    ///     schema: "dev".to_string(),
+    ///     table: "dog".to_string(),
+    ///     from: Some("2020-09-01".parse()?),
+    ///     to: None,
+    ///     limit: Some(100),
+    /// };
+    /// let entries: Vec<TableLogEntry> = harper_client.read_transaction_log(transaction_log_option).await?;
+    /// ```
+    ///
+    pub async fn read_transaction_log(&self, options: TransactionLogOptions) -> Result<Vec<TableLogEntry>, Error> {
+        let map = json!({
+            "operation": "read_transaction_log",
+            "schema": &options.schema,
+            "table": &options.table,
+            "from": options.from.map(|from| from.as_millis()),
+            "to": options.to.map(|to| to.as_millis()),
+            "limit": &options.limit,
+        });
+        let res = Error::check(self.request(&map).await?).await?;
+
+        let entries = match res.json().await? {
+            TableLogResponse::Entries(entries) => entries,
+            TableLogResponse::ByHashValue(groups) => groups.into_values().flatten().collect(),
+        };
+
+        Ok(entries)
     }
 
     /// Get Job
@@ -1491,7 +2351,7 @@ impl Harper {
     /// 
     /// ```
     /// let get_job_option: GetJobOptions = GetJobOptions {
-    ///     id : "d8b70ed4-a62a-45ef-bf86-15508c4ba10a",
+    ///     id : "d8b70ed4-a62a-45ef-bf86-15508c4ba10a".to_string(),
     /// };
     ///
     /// let result = harper_client.get_job(get_job_option).await?;
@@ -1502,13 +2362,7 @@ impl Harper {
         map.insert("operation", "get_job");
         map.insert("id", &options.id);
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -1523,30 +2377,703 @@ impl Harper {
     /// 
     /// ```
     /// let search_jobs_by_start_date_option: JobsByDateOptions = JobsByDateOptions {
-    ///     from_date : "2019-01-01",
-    ///     to_date : "2020-12-30",
+    ///     from_date : "2019-01-01".parse()?,
+    ///     to_date : Utc::now().into(),
     /// };
     ///
     /// let result = harper_client.search_jobs_by_start_date(search_jobs_by_start_date_option).await?;
     /// ```
     /// 
     pub async fn search_jobs_by_start_date(&self, options: JobsByDateOptions) -> Result<reqwest::Response, Error> {
+        let map = json!({
+            "operation": "search_jobs_by_start_date",
+            "from_date": &options.from_date,
+            "to_date": &options.to_date,
+        });
+
+        let res = self.request(&map).await?;
+
+        Ok(res)
+    }
+
+    /// Custom Functions Status
+    /// 
+    /// Whether Custom Functions are enabled, and their port and directory.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// let result = harper_client.custom_functions_status().await?;
+    /// ```
+    /// 
+    pub async fn custom_functions_status(&self) -> Result<reqwest::Response, Error> {
+        let mut map = HashMap::new();
+        map.insert("operation", "custom_functions_status");
+
+        let res = self.request(&map).await?;
+
+        Ok(res)
+    }
+
+    /// Get Custom Functions
+    /// 
+    /// Lists the projects and the files in their `routes` and `helpers` folders.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// let result = harper_client.get_custom_functions().await?;
+    /// ```
+    /// 
+    pub async fn get_custom_functions(&self) -> Result<reqwest::Response, Error> {
+        let mut map = HashMap::new();
+        map.insert("operation", "get_custom_functions");
+
+        let res = self.request(&map).await?;
+
+        Ok(res)
+    }
+
+    /// Get Custom Function
+    /// 
+    /// # Arguments
+    /// 
+    /// * `options`  (required) - CustomFunctionOptions
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// let option: CustomFunctionOptions = CustomFunctionOptions {
+    ///     project: "dogs".to_string(),
+    ///     function_type: CustomFunctionType::Helpers,
+    ///     file: "example".to_string(),
+    /// };
+    /// let result = harper_client.get_custom_function(option).await?;
+    /// ```
+    /// 
+    pub async fn get_custom_function(&self, options: CustomFunctionOptions) -> Result<reqwest::Response, Error> {
+        let map = json!({
+            "operation": "get_custom_function",
+            "project": &options.project,
+            "type": &options.function_type,
+            "file": &options.file,
+        });
+        let res = self.request(&map).await?;
+
+        Ok(res)
+    }
+
+    /// Set Custom Function
+    /// 
+    /// Creates or replaces a file of a project.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `options`  (required) - SetCustomFunctionOptions
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// let option: SetCustomFunctionOptions = SetCustomFunctionOptions {
+    ///     project: "dogs".to_string(),
+    ///     function_type: CustomFunctionType::Helpers,
+    ///     file: "example".to_string(),
+    ///     function_content: std::fs::read_to_string("helpers/example.js")?,
+    /// };
+    /// let result = harper_client.set_custom_function(option).await?;
+    /// ```
+    /// 
+    pub async fn set_custom_function(&self, options: SetCustomFunctionOptions) -> Result<reqwest::Response, Error> {
+        let map = json!({
+            "operation": "set_custom_function",
+            "project": &options.project,
+            "type": &options.function_type,
+            "file": &options.file,
+            "function_content": &options.function_content,
+        });
+        let res = self.request(&map).await?;
+
+        Ok(res)
+    }
+
+    /// Drop Custom Function
+    /// 
+    /// # Arguments
+    /// 
+    /// * `options`  (required) - CustomFunctionOptions
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// let option: CustomFunctionOptions = CustomFunctionOptions {
+    ///     project: "dogs".to_string(),
+    ///     function_type: CustomFunctionType::Helpers,
+    ///     file: "example".to_string(),
+    /// };
+    /// let result = harper_client.drop_custom_function(option).await?;
+    /// ```
+    /// 
+    pub async fn drop_custom_function(&self, options: CustomFunctionOptions) -> Result<reqwest::Response, Error> {
+        let map = json!({
+            "operation": "drop_custom_function",
+            "project": &options.project,
+            "type": &options.function_type,
+            "file": &options.file,
+        });
+        let res = self.request(&map).await?;
+
+        Ok(res)
+    }
+
+    /// Add Custom Function Project
+    /// 
+    /// Creates a project from the server's template.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `options`  (required) - CustomFunctionProjectOptions
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// let option: CustomFunctionProjectOptions = CustomFunctionProjectOptions {
+    ///     project: "dogs".to_string(),
+    /// };
+    /// let result = harper_client.add_custom_function_project(option).await?;
+    /// ```
+    /// 
+    pub async fn add_custom_function_project(&self, options: CustomFunctionProjectOptions) -> Result<reqwest::Response, Error> {
+        let mut map = HashMap::new();
+        map.insert("operation", "add_custom_function_project");
+        map.insert("project", &options.project);
+
+        let res = self.request(&map).await?;
+
+        Ok(res)
+    }
+
+    /// Drop Custom Function Project
+    /// 
+    /// # Arguments
+    /// 
+    /// * `options`  (required) - CustomFunctionProjectOptions
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// let option: CustomFunctionProjectOptions = CustomFunctionProjectOptions {
+    ///     project: "dogs".to_string(),
+    /// };
+    /// let result = harper_client.drop_custom_function_project(option).await?;
+    /// ```
+    /// 
+    pub async fn drop_custom_function_project(&self, options: CustomFunctionProjectOptions) -> Result<reqwest::Response, Error> {
+        let mut map = HashMap::new();
+        map.insert("operation", "drop_custom_function_project");
+        map.insert("project", &options.project);
+
+        let res = self.request(&map).await?;
+
+        Ok(res)
+    }
+
+    /// Package Custom Function Project
+    /// 
+    /// Answers with the project as a base64 encoded tar in `payload`, ready for
+    /// `deploy_custom_function_project` on another instance.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `options`  (required) - PackageCustomFunctionProjectOptions
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// let option: PackageCustomFunctionProjectOptions = PackageCustomFunctionProjectOptions {
+    ///     project: "dogs".to_string(),
+    ///     skip_node_modules: Some(true),
+    /// };
+    /// let result = harper_client.package_custom_function_project(option).await?;
+    /// ```
+    /// 
+    pub async fn package_custom_function_project(&self, options: PackageCustomFunctionProjectOptions) -> Result<reqwest::Response, Error> {
+        let map = json!({
+            "operation": "package_custom_function_project",
+            "project": &options.project,
+            "skip_node_modules": &options.skip_node_modules,
+        });
+        let res = self.request(&map).await?;
+
+        Ok(res)
+    }
+
+    /// Deploy Custom Function Project
+    /// 
+    /// Creates or replaces a project from a base64 encoded tar. Build the
+    /// payload from a local directory with `package_directory`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `options`  (required) - DeployCustomFunctionProjectOptions
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// let option: DeployCustomFunctionProjectOptions = DeployCustomFunctionProjectOptions {
+    ///     project: "dogs".to_string(),
+    ///     payload: harperdb::package_directory("./dogs")?,
+    /// };
+    /// let result = harper_client.deploy_custom_function_project(option).await?;
+    /// ```
+    /// 
+    pub async fn deploy_custom_function_project(&self, options: DeployCustomFunctionProjectOptions) -> Result<reqwest::Response, Error> {
+        let map = json!({
+            "operation": "deploy_custom_function_project",
+            "project": &options.project,
+            "payload": &options.payload,
+        });
+        let res = self.request(&map).await?;
+
+        Ok(res)
+    }
+
+    /// Deploy Component
+    /// 
+    /// Installs or replaces a component, from a base64 encoded tar or from an
+    /// npm package or git reference.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `options`  (required) - DeployComponentOptions
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// let option: DeployComponentOptions = DeployComponentOptions {
+    ///     project: "dogs".to_string(),
+    ///     package: Some("HarperDB/application-template".to_string()),
+    ///     restart: Some(true),
+    ///     ..Default::default()
+    /// };
+    /// let result = harper_client.deploy_component(option).await?;
+    /// ```
+    /// 
+    pub async fn deploy_component(&self, options: DeployComponentOptions) -> Result<reqwest::Response, Error> {
+        let mut map = json!({
+            "operation": "deploy_component",
+            "project": &options.project,
+        });
+        if let Some(payload) = &options.payload {
+            map["payload"] = json!(payload);
+        }
+        if let Some(package) = &options.package {
+            map["package"] = json!(package);
+        }
+        if let Some(restart) = options.restart {
+            map["restart"] = json!(restart);
+        }
+        if let Some(replicated) = options.replicated {
+            map["replicated"] = json!(replicated);
+        }
+        let res = self.request(&map).await?;
+
+        Ok(res)
+    }
+
+    /// Deploy a local directory as a component
+    /// 
+    /// Tars `dir` with `package_directory` and sends it with `deploy_component`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `project`  (required) - &str
+    /// * `dir`  (required) - impl AsRef<Path>
+    /// * `restart`  (required) - bool, restart the server to load the component
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// let result = harper_client.deploy_component_directory("dogs", "./dogs", true).await?;
+    /// ```
+    /// 
+    pub async fn deploy_component_directory<P: AsRef<Path>>(
+        &self,
+        project: &str,
+        dir: P,
+        restart: bool,
+    ) -> Result<reqwest::Response, Error> {
+        let option = DeployComponentOptions {
+            project: project.to_string(),
+            payload: Some(package_directory(dir)?),
+            restart: Some(restart),
+            ..Default::default()
+        };
+
+        self.deploy_component(option).await
+    }
+
+    /// Package Component
+    /// 
+    /// Answers with the component as a base64 encoded tar in `payload`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `options`  (required) - PackageComponentOptions
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// let option: PackageComponentOptions = PackageComponentOptions {
+    ///     project: "dogs".to_string(),
+    ///     skip_node_modules: Some(true),
+    /// };
+    /// let result = harper_client.package_component(option).await?;
+    /// ```
+    /// 
+    pub async fn package_component(&self, options: PackageComponentOptions) -> Result<reqwest::Response, Error> {
+        let map = json!({
+            "operation": "package_component",
+            "project": &options.project,
+            "skip_node_modules": &options.skip_node_modules,
+        });
+        let res = self.request(&map).await?;
+
+        Ok(res)
+    }
+
+    /// Get Components
+    /// 
+    /// Lists the installed components as a tree of their files.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// let result = harper_client.get_components().await?;
+    /// ```
+    /// 
+    pub async fn get_components(&self) -> Result<reqwest::Response, Error> {
         let mut map = HashMap::new();
-        map.insert("operation", "search_jobs_by_start_date");
-        map.insert("from_date", &options.from_date);
-        map.insert("to_date", &options.to_date);
-
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        map.insert("operation", "get_components");
+
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
 
+    /// Get Component File
+    /// 
+    /// Answers with the file content in `message`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `options`  (required) - ComponentFileOptions
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// let option: ComponentFileOptions = ComponentFileOptions {
+    ///     project: "dogs".to_string(),
+    ///     file: "resources.js".to_string(),
+    /// };
+    /// let result = harper_client.get_component_file(option).await?;
+    /// ```
+    /// 
+    pub async fn get_component_file(&self, options: ComponentFileOptions) -> Result<reqwest::Response, Error> {
+        let mut map = HashMap::new();
+        map.insert("operation", "get_component_file");
+        map.insert("project", &options.project);
+        map.insert("file", &options.file);
+
+        let res = self.request(&map).await?;
+
+        Ok(res)
+    }
+
+    /// Set Component File
+    /// 
+    /// Creates or replaces a file of a component.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `options`  (required) - SetComponentFileOptions
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// let option: SetComponentFileOptions = SetComponentFileOptions {
+    ///     project: "dogs".to_string(),
+    ///     file: "resources.js".to_string(),
+    ///     payload: std::fs::read_to_string("dogs/resources.js")?,
+    ///     encoding: None,
+    /// };
+    /// let result = harper_client.set_component_file(option).await?;
+    /// ```
+    /// 
+    pub async fn set_component_file(&self, options: SetComponentFileOptions) -> Result<reqwest::Response, Error> {
+        let mut map = json!({
+            "operation": "set_component_file",
+            "project": &options.project,
+            "file": &options.file,
+            "payload": &options.payload,
+        });
+        if let Some(encoding) = &options.encoding {
+            map["encoding"] = json!(encoding);
+        }
+        let res = self.request(&map).await?;
+
+        Ok(res)
+    }
+
+    /// Drop Component
+    /// 
+    /// Removes a component, or one file of it.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `options`  (required) - DropComponentOptions
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// let option: DropComponentOptions = DropComponentOptions {
+    ///     project: "dogs".to_string(),
+    ///     file: None,
+    /// };
+    /// let result = harper_client.drop_component(option).await?;
+    /// ```
+    /// 
+    pub async fn drop_component(&self, options: DropComponentOptions) -> Result<reqwest::Response, Error> {
+        let mut map = json!({
+            "operation": "drop_component",
+            "project": &options.project,
+        });
+        if let Some(file) = &options.file {
+            map["file"] = json!(file);
+        }
+        let res = self.request(&map).await?;
+
+        Ok(res)
+    }
+
+    /// Get Configuration
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// let configuration: ServerConfiguration = harper_client.get_configuration().await?;
+    /// let level = configuration.logging.and_then(|logging| logging.level);
+    /// ```
+    /// 
+    pub async fn get_configuration(&self) -> Result<ServerConfiguration, Error> {
+        let mut map = HashMap::new();
+        map.insert("operation", "get_configuration");
+
+        let res = Error::check(self.request(&map).await?).await?;
+
+        Ok(res.json().await?)
+    }
+
+    /// Set Configuration
+    /// 
+    /// Changes the settings set in `update` and leaves the others alone. Most
+    /// settings take effect after a restart.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `update`  (required) - &ServerConfiguration
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// let update = ServerConfiguration {
+    ///     logging: Some(LoggingConfiguration {
+    ///         level: Some("warn".to_string()),
+    ///         ..Default::default()
+    ///     }),
+    ///     ..Default::default()
+    /// };
+    /// let result = harper_client.set_configuration(&update).await?;
+    /// ```
+    /// 
+    pub async fn set_configuration(&self, update: &ServerConfiguration) -> Result<reqwest::Response, Error> {
+        let mut map = update.flatten();
+        map.insert("operation".to_string(), json!("set_configuration"));
+
+        let res = self.request(&map).await?;
+
+        Ok(res)
+    }
+
+    /// The settings `set_configuration` would change, compared with the
+    /// server's current configuration
+    /// 
+    /// # Arguments
+    /// 
+    /// * `update`  (required) - &ServerConfiguration
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// for change in harper_client.diff_configuration(&update).await? {
+    ///     println!("{}", change);
+    /// }
+    /// ```
+    /// 
+    pub async fn diff_configuration(&self, update: &ServerConfiguration) -> Result<Vec<ConfigurationChange>, Error> {
+        let current = self.get_configuration().await?;
+
+        Ok(current.diff(update))
+    }
+
+    /// Restart
+    /// 
+    /// Restarts the server. It answers before going down, so follow up with
+    /// `wait_until_ready` on the restarted node once it had time to stop.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// let result = harper_client.restart().await?;
+    /// ```
+    /// 
+    pub async fn restart(&self) -> Result<reqwest::Response, Error> {
+        let mut map = HashMap::new();
+        map.insert("operation", "restart");
+
+        let res = self.request(&map).await?;
+
+        Ok(res)
+    }
+
+    /// Restart Service
+    /// 
+    /// # Arguments
+    /// 
+    /// * `options`  (required) - RestartServiceOptions
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// let option: RestartServiceOptions = RestartServiceOptions {
+    ///     service: Service::HttpWorkers,
+    /// };
+    /// let result = harper_client.restart_service(option).await?;
+    /// ```
+    /// 
+    pub async fn restart_service(&self, options: RestartServiceOptions) -> Result<reqwest::Response, Error> {
+        let map = json!({
+            "operation": "restart_service",
+            "service": &options.service,
+        });
+        let res = self.request(&map).await?;
+
+        Ok(res)
+    }
+
+    /// Poll `node` with `user_info` until it answers, for at most `timeout`
+    /// 
+    /// The probe goes to `node` only, through the middlewares but without
+    /// failing over, so a healthy peer cannot answer for a node that is still
+    /// down. Any answer other than a server error counts as ready, a rejected
+    /// login too. Fails with `Error::NotReady` when `timeout` runs out.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `node`  (required) - URL of the node to wait for, e.g. the `url` of the config
+    /// * `timeout`  (required) - How long to keep polling
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// harper_client.restart().await?;
+    /// tokio::time::delay_for(Duration::from_secs(5)).await;
+    /// harper_client.wait_until_ready("http://localhost:9925", Duration::from_secs(60)).await?;
+    /// ```
+    /// 
+    pub async fn wait_until_ready(&self, node: &str, timeout: Duration) -> Result<(), Error> {
+        const POLL_INTERVAL: Duration = Duration::from_millis(500);
+        let url = config::normalize_url(node)?;
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        loop {
+            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+            if remaining == Duration::from_secs(0) {
+                return Err(Error::NotReady(timeout));
+            }
+            let request = OperationRequest {
+                operation: json!({ "operation": "user_info" }),
+                url: url.clone(),
+                headers: Default::default(),
+                timeout: remaining.min(self.config.timeouts.lookup),
+            };
+            match Next::new(&self.client, &self.middlewares).run(request).await {
+                Ok(res) if !res.status().is_server_error() => return Ok(()),
+                Ok(_) | Err(Error::Http(_)) | Err(Error::Timeout(_)) => {}
+                Err(error) => return Err(error),
+            }
+            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+            tokio::time::delay_for(remaining.min(POLL_INTERVAL)).await;
+        }
+    }
+
+    /// Create Authentication Tokens
+    /// 
+    /// Mints an operation token and a refresh token for a user, checking the
+    /// user's password.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `username`  (required) - &str
+    /// * `password`  (required) - &str
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// let tokens: AuthenticationTokens = harper_client.create_authentication_tokens("dev_user", "secret").await?;
+    /// let expires_at = TokenClaims::decode(&tokens.operation_token)?.expires_at;
+    /// ```
+    /// 
+    pub async fn create_authentication_tokens(&self, username: &str, password: &str) -> Result<AuthenticationTokens, Error> {
+        let mut map = HashMap::new();
+        map.insert("operation", "create_authentication_tokens");
+        map.insert("username", username);
+        map.insert("password", password);
+
+        let res = Error::check(self.request(&map).await?).await?;
+
+        Ok(res.json().await?)
+    }
+
+    /// Refresh Operation Token
+    /// 
+    /// Mints a new operation token from a refresh token.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `refresh_token`  (required) - &str
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// let operation_token: String = harper_client.refresh_operation_token(&tokens.refresh_token).await?;
+    /// ```
+    /// 
+    pub async fn refresh_operation_token(&self, refresh_token: &str) -> Result<String, Error> {
+        #[derive(Deserialize)]
+        struct Refreshed {
+            operation_token: String,
+        }
+
+        let mut map = HashMap::new();
+        map.insert("operation", "refresh_operation_token");
+        map.insert("refresh_token", refresh_token);
+
+        let res = Error::check(self.request(&map).await?).await?;
+        let refreshed: Refreshed = res.json().await?;
+
+        Ok(refreshed.operation_token)
+    }
+
     /// Registration Info 
     /// 
     /// # Examples
@@ -1559,13 +3086,7 @@ impl Harper {
         let mut map = HashMap::new();
         map.insert("operation", "registration_info");
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -1582,13 +3103,7 @@ impl Harper {
         let mut map = HashMap::new();
         map.insert("operation", "get_fingerprint");
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -1615,13 +3130,7 @@ impl Harper {
         map.insert("key", &options.key);
         map.insert("company", &options.company);
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -1651,13 +3160,7 @@ impl Harper {
         map.insert("port", &options.port);
         map.insert("host", &options.host);
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -1687,13 +3190,7 @@ impl Harper {
         map.insert("port", &options.port);
         map.insert("host", &options.host);
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -1719,13 +3216,7 @@ impl Harper {
         map.insert("operation", "remove_node");
         map.insert("name", &options.name);
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
@@ -1742,13 +3233,7 @@ impl Harper {
         let mut map = HashMap::new();
         map.insert("operation", "cluster_status");
 
-        let res = self
-            .client
-            .post(self.config.url)
-            .basic_auth(&self.config.username, Some(&self.config.password))
-            .json(&map)
-            .send()
-            .await?;
+        let res = self.request(&map).await?;
 
         Ok(res)
     }
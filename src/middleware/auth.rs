@@ -0,0 +1,30 @@
+use super::{BoxFuture, Middleware, Next, OperationRequest};
+use crate::error::Error;
+use reqwest::header::{HeaderValue, AUTHORIZATION};
+
+/// Authenticates every request with HTTP basic auth
+pub struct BasicAuth {
+    header: HeaderValue,
+}
+
+impl BasicAuth {
+    pub fn new(username: &str, password: &str) -> Self {
+        let credentials = base64::encode(format!("{}:{}", username, password));
+        let mut header = HeaderValue::from_str(&format!("Basic {}", credentials))
+            .expect("base64 is always a valid header value");
+        header.set_sensitive(true);
+
+        BasicAuth { header }
+    }
+}
+
+impl Middleware for BasicAuth {
+    fn handle<'a>(
+        &'a self,
+        mut request: OperationRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<reqwest::Response, Error>> {
+        request.headers.insert(AUTHORIZATION, self.header.clone());
+        Box::pin(next.run(request))
+    }
+}
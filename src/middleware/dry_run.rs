@@ -0,0 +1,125 @@
+use super::{BoxFuture, Middleware, Next, OperationRequest};
+use crate::error::Error;
+use crate::operation::OperationClass;
+use serde_json::Value;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+struct Recording {
+    operations: Vec<Value>,
+    file: Option<File>,
+}
+
+/// Captures operations instead of sending them
+///
+/// Each captured operation is answered with `200 OK` and the body
+/// `{"message": "dry run, operation not sent"}`, so code expecting a specific
+/// response body fails to decode it. Only the operation JSON is recorded, never
+/// the headers carrying the credentials. Clones share the recording.
+///
+/// Register it last (see `Harper::dry_run`) so the other middlewares still run.
+/// `Harper::dry_run` also leaves the metadata cache out of the chain, so its
+/// placeholder answers are never cached as descriptions.
+#[derive(Clone)]
+pub struct DryRun {
+    recording: Arc<Mutex<Recording>>,
+    send_reads: bool,
+}
+
+impl DryRun {
+    /// Capture into memory; see `operations`
+    pub fn new() -> Self {
+        DryRun {
+            recording: Arc::new(Mutex::new(Recording {
+                operations: Vec::new(),
+                file: None,
+            })),
+            send_reads: false,
+        }
+    }
+
+    /// Also append every captured operation to `path` as one line of JSON
+    /// (NDJSON), replacing the file if it exists
+    pub fn to_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Ok(DryRun::new().with_file(File::create(path)?))
+    }
+
+    /// Like `to_file`, but append to an existing file
+    pub fn append_to_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(DryRun::new().with_file(file))
+    }
+
+    fn with_file(self, file: File) -> Self {
+        self.recording.lock().unwrap().file = Some(file);
+        self
+    }
+
+    /// Send read operations (`OperationClass::Read`) for real, so code that
+    /// looks data up before changing it keeps working. Off by default.
+    pub fn send_reads(mut self, send_reads: bool) -> Self {
+        self.send_reads = send_reads;
+        self
+    }
+
+    /// The operations captured so far, in order
+    pub fn operations(&self) -> Vec<Value> {
+        self.recording.lock().unwrap().operations.clone()
+    }
+
+    /// Read a recording written by `to_file`, for `Harper::replay`
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<Value>, Error> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut operations = Vec::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let operation = serde_json::from_str(&line).map_err(|error| {
+                Error::Json(serde::de::Error::custom(format!("line {}: {}", index + 1, error)))
+            })?;
+            operations.push(operation);
+        }
+        Ok(operations)
+    }
+
+    fn record(&self, operation: Value) -> Result<(), Error> {
+        let mut recording = self.recording.lock().unwrap();
+        if let Some(file) = &mut recording.file {
+            let line = format!("{}\n", serde_json::to_string(&operation)?);
+            file.write_all(line.as_bytes())?;
+        }
+        recording.operations.push(operation);
+        Ok(())
+    }
+}
+
+impl Default for DryRun {
+    fn default() -> Self {
+        DryRun::new()
+    }
+}
+
+impl Middleware for DryRun {
+    fn handle<'a>(
+        &'a self,
+        request: OperationRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<reqwest::Response, Error>> {
+        Box::pin(async move {
+            if self.send_reads && OperationClass::of(&request.operation) == OperationClass::Read {
+                return next.run(request).await;
+            }
+
+            self.record(request.operation)?;
+            let res = http::Response::builder()
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(r#"{"message":"dry run, operation not sent"}"#)
+                .expect("a static status and header are valid");
+            Ok(reqwest::Response::from(res))
+        })
+    }
+}
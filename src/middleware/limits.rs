@@ -0,0 +1,181 @@
+use super::{BoxFuture, Middleware, Next, OperationRequest};
+use crate::config::ConfigError;
+use crate::error::Error;
+use crate::operation::OperationClass;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::time::Instant;
+
+/// Limits for one budget of requests. Unset fields do not limit.
+///
+/// A request holds its concurrency slot until its response headers arrive.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+pub struct RateLimit {
+    /// Requests in flight at the same time
+    pub max_concurrent: Option<usize>,
+    /// Sustained request rate, as a token bucket refilled at this rate
+    pub requests_per_second: Option<f64>,
+    /// Requests that may start back to back after an idle period; at least 1
+    pub burst: Option<usize>,
+}
+
+/// Client-side limits on the requests a `Harper` client sends
+///
+/// Every request is charged to `all`, and also to the budget of its
+/// `OperationClass` when one is set.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+pub struct RateLimits {
+    pub all: RateLimit,
+    pub reads: Option<RateLimit>,
+    pub writes: Option<RateLimit>,
+    pub admin: Option<RateLimit>,
+}
+
+impl RateLimit {
+    fn validate(&self, budget: &str) -> Result<(), ConfigError> {
+        let invalid = |reason: &str| ConfigError::InvalidLimit {
+            budget: budget.to_string(),
+            reason: reason.to_string(),
+        };
+        if let Some(per_second) = self.requests_per_second {
+            if Duration::try_from_secs_f64(1.0 / per_second).is_err() {
+                return Err(invalid("requests_per_second must be a positive number"));
+            }
+        }
+        if self.burst == Some(0) {
+            return Err(invalid("burst must be at least 1"));
+        }
+        Ok(())
+    }
+}
+
+impl RateLimits {
+    /// Check that every rate is a positive number and every burst at least 1;
+    /// `Harper::try_new` fails with `Error::Config` otherwise
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.all.validate("all")?;
+        let budgets = [("reads", &self.reads), ("writes", &self.writes), ("admin", &self.admin)];
+        for (budget, limit) in budgets.iter() {
+            if let Some(limit) = limit {
+                limit.validate(budget)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn is_unlimited(&self) -> bool {
+        let unlimited = |limit: &RateLimit| limit.max_concurrent.is_none() && limit.requests_per_second.is_none();
+        unlimited(&self.all) && [self.reads, self.writes, self.admin].iter().flatten().all(unlimited)
+    }
+}
+
+struct TokenBucket {
+    interval: Duration,
+    burst: u32,
+    // The earliest time the next caller may start.
+    next: Instant,
+}
+
+struct Budget {
+    concurrency: Option<Semaphore>,
+    rate: Option<Mutex<TokenBucket>>,
+}
+
+impl Budget {
+    fn new(limit: &RateLimit) -> Self {
+        Budget {
+            concurrency: limit.max_concurrent.map(|permits| Semaphore::new(permits.max(1))),
+            rate: limit.requests_per_second.map(|per_second| {
+                Mutex::new(TokenBucket {
+                    interval: Duration::from_secs_f64(1.0 / per_second),
+                    burst: u32::try_from(limit.burst.unwrap_or(1).max(1)).unwrap_or(u32::MAX),
+                    next: Instant::now(),
+                })
+            }),
+        }
+    }
+
+    /// Wait for a rate token, then for a concurrency slot. Both queues are first
+    /// come, first served: rate tokens are handed out as reservations in arrival
+    /// order and the semaphore queues waiters fairly.
+    async fn acquire(&self) -> Option<tokio::sync::SemaphorePermit<'_>> {
+        if let Some(rate) = &self.rate {
+            let start_at = {
+                let mut bucket = rate.lock().unwrap();
+                let interval = bucket.interval;
+                // An idle bucket refills up to `burst` tokens.
+                let now = Instant::now();
+                let max_credit = interval.checked_mul(bucket.burst - 1).unwrap_or(Duration::MAX);
+                if now.saturating_duration_since(bucket.next) > max_credit {
+                    bucket.next = now - max_credit;
+                }
+                let start_at = bucket.next;
+                bucket.next += interval;
+                start_at
+            };
+            tokio::time::delay_until(start_at).await;
+        }
+        match &self.concurrency {
+            Some(semaphore) => Some(semaphore.acquire().await),
+            None => None,
+        }
+    }
+}
+
+/// Queues requests so they stay within configured `RateLimits`
+///
+/// `Harper::new` registers one automatically when `HarperConfig.limits` sets any
+/// limit.
+pub struct RateLimiter {
+    all: Budget,
+    reads: Option<Budget>,
+    writes: Option<Budget>,
+    admin: Option<Budget>,
+}
+
+impl RateLimiter {
+    /// # Panics
+    ///
+    /// Panics when `limits.validate()` fails.
+    pub fn new(limits: &RateLimits) -> Self {
+        RateLimiter {
+            all: Budget::new(&limits.all),
+            reads: limits.reads.as_ref().map(Budget::new),
+            writes: limits.writes.as_ref().map(Budget::new),
+            admin: limits.admin.as_ref().map(Budget::new),
+        }
+    }
+
+    fn budget(&self, class: OperationClass) -> Option<&Budget> {
+        match class {
+            OperationClass::Read => self.reads.as_ref(),
+            OperationClass::Write => self.writes.as_ref(),
+            OperationClass::Admin => self.admin.as_ref(),
+        }
+    }
+}
+
+impl Middleware for RateLimiter {
+    fn handle<'a>(
+        &'a self,
+        request: OperationRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<reqwest::Response, Error>> {
+        Box::pin(async move {
+            let class_permit = match self.budget(OperationClass::of(&request.operation)) {
+                Some(budget) => budget.acquire().await,
+                None => None,
+            };
+            let permit = self.all.acquire().await;
+
+            let res = next.run(request).await;
+
+            drop(permit);
+            drop(class_permit);
+            res
+        })
+    }
+}
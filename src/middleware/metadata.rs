@@ -0,0 +1,139 @@
+use super::{BoxFuture, Middleware, Next, OperationRequest};
+use crate::error::Error;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Key {
+    All,
+    Schema(String),
+    Table(String, String),
+}
+
+struct Entry {
+    body: Vec<u8>,
+    fetched: Instant,
+}
+
+/// Serves `describe_all`, `describe_schema` and `describe_table` from memory
+/// for `ttl` after a successful answer
+///
+/// Schema changes sent through the client (`create_schema`, `drop_schema`,
+/// `create_table`, `drop_table`, `create_attribute` and `drop_attribute`)
+/// invalidate the affected entries when they succeed. Changes made by other
+/// clients, record counts and attributes added by inserts show up once an entry
+/// expires or is invalidated.
+///
+/// `Harper::new` registers one when `HarperConfig.metadata_ttl` is set. Clones
+/// share the cached entries.
+#[derive(Clone)]
+pub struct MetadataCache {
+    ttl: Duration,
+    entries: Arc<Mutex<HashMap<Key, Entry>>>,
+}
+
+impl MetadataCache {
+    pub fn new(ttl: Duration) -> Self {
+        MetadataCache {
+            ttl,
+            entries: Default::default(),
+        }
+    }
+
+    /// Drop every entry, so the next describe of any kind asks the server
+    pub fn invalidate_all(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// Drop the entries covering `schema` and its tables, and `describe_all`
+    pub fn invalidate_schema(&self, schema: &str) {
+        self.entries.lock().unwrap().retain(|key, _| match key {
+            Key::All => false,
+            Key::Schema(name) | Key::Table(name, _) => name != schema,
+        });
+    }
+
+    /// Drop the entries covering `schema.table`, its schema and `describe_all`
+    pub fn invalidate_table(&self, schema: &str, table: &str) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.remove(&Key::All);
+        entries.remove(&Key::Schema(schema.to_string()));
+        entries.remove(&Key::Table(schema.to_string(), table.to_string()));
+    }
+
+    fn get(&self, key: &Key) -> Option<Vec<u8>> {
+        let entries = self.entries.lock().unwrap();
+        let entry = entries.get(key)?;
+        if entry.fetched.elapsed() < self.ttl {
+            Some(entry.body.clone())
+        } else {
+            None
+        }
+    }
+
+    fn put(&self, key: Key, body: Vec<u8>) {
+        let entry = Entry {
+            body,
+            fetched: Instant::now(),
+        };
+        self.entries.lock().unwrap().insert(key, entry);
+    }
+}
+
+fn field(operation: &Value, name: &str) -> String {
+    operation.get(name).and_then(Value::as_str).unwrap_or_default().to_string()
+}
+
+fn cached_response(body: Vec<u8>) -> reqwest::Response {
+    let res = http::Response::builder()
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body)
+        .expect("a static status and header are valid");
+    reqwest::Response::from(res)
+}
+
+impl Middleware for MetadataCache {
+    fn handle<'a>(
+        &'a self,
+        request: OperationRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<reqwest::Response, Error>> {
+        Box::pin(async move {
+            let schema = field(&request.operation, "schema");
+            let table = field(&request.operation, "table");
+            let key = match request.operation_name() {
+                "describe_all" => Key::All,
+                "describe_schema" => Key::Schema(schema),
+                "describe_table" => Key::Table(schema, table),
+                "create_schema" | "drop_schema" => {
+                    let res = next.run(request).await?;
+                    if res.status().is_success() {
+                        self.invalidate_schema(&schema);
+                    }
+                    return Ok(res);
+                }
+                "create_table" | "drop_table" | "create_attribute" | "drop_attribute" => {
+                    let res = next.run(request).await?;
+                    if res.status().is_success() {
+                        self.invalidate_table(&schema, &table);
+                    }
+                    return Ok(res);
+                }
+                _ => return next.run(request).await,
+            };
+
+            if let Some(body) = self.get(&key) {
+                return Ok(cached_response(body));
+            }
+            let res = next.run(request).await?;
+            if !res.status().is_success() {
+                return Ok(res);
+            }
+            let body = res.bytes().await?.to_vec();
+            self.put(key, body.clone());
+            Ok(cached_response(body))
+        })
+    }
+}
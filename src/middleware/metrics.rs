@@ -0,0 +1,79 @@
+use super::{BoxFuture, Middleware, Next, OperationRequest};
+use crate::error::Error;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Counters for one operation name
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OperationMetrics {
+    /// Requests sent, including retries and failover attempts
+    pub requests: u64,
+    /// Requests answered with a 4xx or 5xx status
+    pub error_responses: u64,
+    /// Requests that never got a response
+    pub failures: u64,
+    /// Time spent waiting for responses
+    pub total_latency: Duration,
+}
+
+/// Collects per-operation request counts and latency
+///
+/// Clones share their counters, so keep one clone to read the numbers after
+/// registering another on the client.
+///
+/// ```
+/// let metrics = Metrics::new();
+/// let harper_client = Harper::new(config).with_middleware(metrics.clone());
+/// // ...
+/// println!("{:#?}", metrics.snapshot());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    operations: Arc<Mutex<HashMap<String, OperationMetrics>>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Metrics::default()
+    }
+
+    /// Counters so far, keyed by operation name
+    pub fn snapshot(&self) -> HashMap<String, OperationMetrics> {
+        self.operations.lock().unwrap().clone()
+    }
+
+    pub fn reset(&self) {
+        self.operations.lock().unwrap().clear();
+    }
+}
+
+impl Middleware for Metrics {
+    fn handle<'a>(
+        &'a self,
+        request: OperationRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<reqwest::Response, Error>> {
+        Box::pin(async move {
+            let operation = request.operation_name().to_string();
+            let started = Instant::now();
+            let res = next.run(request).await;
+            let latency = started.elapsed();
+
+            let mut operations = self.operations.lock().unwrap();
+            let metrics = operations.entry(operation).or_default();
+            metrics.requests += 1;
+            metrics.total_latency += latency;
+            match &res {
+                Ok(res) if res.status().is_client_error() || res.status().is_server_error() => {
+                    metrics.error_responses += 1
+                }
+                Ok(_) => {}
+                Err(_) => metrics.failures += 1,
+            }
+            drop(operations);
+
+            res
+        })
+    }
+}
@@ -0,0 +1,115 @@
+//! Hooks around every request the client sends.
+//!
+//! A [`Middleware`] receives the outgoing [`OperationRequest`] and the rest of the
+//! chain as [`Next`]. It can change the operation JSON, the target URL or the
+//! headers, call `next.run(request)` (any number of times, or not at all), and
+//! observe or replace the response.
+//!
+//! Middlewares run in registration order. `Harper::new` registers [`BasicAuth`]
+//! with the configured credentials first, so later middlewares see the
+//! `Authorization` header and may replace it, followed by a [`MetadataCache`] when
+//! `HarperConfig.metadata_ttl` is set and a [`RateLimiter`] when
+//! `HarperConfig.limits` sets any limit.
+//!
+//! ```
+//! struct Delay(Duration);
+//!
+//! impl Middleware for Delay {
+//!     fn handle<'a>(&'a self, request: OperationRequest, next: Next<'a>) -> BoxFuture<'a, Result<reqwest::Response, Error>> {
+//!         Box::pin(async move {
+//!             tokio::time::delay_for(self.0).await;
+//!             next.run(request).await
+//!         })
+//!     }
+//! }
+//!
+//! let harper_client = Harper::new(config).with_middleware(Delay(Duration::from_millis(200)));
+//! ```
+
+mod auth;
+mod dry_run;
+mod limits;
+mod metadata;
+mod metrics;
+mod retry;
+
+pub use auth::BasicAuth;
+pub use dry_run::DryRun;
+pub use futures_util::future::BoxFuture;
+pub use limits::{RateLimit, RateLimiter, RateLimits};
+pub use metadata::MetadataCache;
+pub use metrics::{Metrics, OperationMetrics};
+pub use retry::Retry;
+
+use crate::error::Error;
+use reqwest::header::HeaderMap;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// One attempt at sending an operation to a node
+#[derive(Debug, Clone)]
+pub struct OperationRequest {
+    /// The operation body, e.g. `{"operation": "describe_all"}`
+    pub operation: Value,
+    /// The node URL the operation is posted to
+    pub url: String,
+    pub headers: HeaderMap,
+    /// Time allowed for the whole exchange, including reading the body
+    pub timeout: Duration,
+}
+
+impl OperationRequest {
+    /// The `operation` field of the body, e.g. `"insert"`
+    pub fn operation_name(&self) -> &str {
+        self.operation
+            .get("operation")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+    }
+}
+
+pub trait Middleware: Send + Sync {
+    fn handle<'a>(
+        &'a self,
+        request: OperationRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<reqwest::Response, Error>>;
+}
+
+/// The remainder of the middleware chain, ending in the HTTP call
+#[derive(Clone, Copy)]
+pub struct Next<'a> {
+    client: &'a reqwest::Client,
+    middlewares: &'a [Arc<dyn Middleware>],
+}
+
+impl<'a> Next<'a> {
+    pub(crate) fn new(client: &'a reqwest::Client, middlewares: &'a [Arc<dyn Middleware>]) -> Self {
+        Next { client, middlewares }
+    }
+
+    pub async fn run(self, request: OperationRequest) -> Result<reqwest::Response, Error> {
+        match self.middlewares.split_first() {
+            Some((middleware, rest)) => {
+                let next = Next {
+                    client: self.client,
+                    middlewares: rest,
+                };
+                middleware.handle(request, next).await
+            }
+            None => {
+                let res = self
+                    .client
+                    .post(&request.url)
+                    .headers(request.headers)
+                    .timeout(request.timeout)
+                    .json(&request.operation)
+                    .send()
+                    .await?;
+
+                Ok(res)
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use super::{BoxFuture, Middleware, Next, OperationRequest};
+use crate::error::Error;
+use reqwest::StatusCode;
+use std::time::Duration;
+
+/// Retries operations the server turned away without processing them
+///
+/// A response of `429 Too Many Requests`, `502 Bad Gateway`, `503 Service
+/// Unavailable` or `504 Gateway Timeout` is retried up to `max_retries` times,
+/// waiting `backoff` before the first retry and doubling the wait each time.
+/// Connection failures are left to node failover.
+pub struct Retry {
+    max_retries: usize,
+    backoff: Duration,
+}
+
+impl Retry {
+    pub fn new(max_retries: usize) -> Self {
+        Retry {
+            max_retries,
+            backoff: Duration::from_millis(100),
+        }
+    }
+
+    /// Wait before the first retry, doubled for every later one
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+impl Middleware for Retry {
+    fn handle<'a>(
+        &'a self,
+        request: OperationRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<reqwest::Response, Error>> {
+        Box::pin(async move {
+            let mut wait = self.backoff;
+            for _ in 0..self.max_retries {
+                let res = next.run(request.clone()).await?;
+                if !is_retryable(res.status()) {
+                    return Ok(res);
+                }
+                tokio::time::delay_for(wait).await;
+                wait *= 2;
+            }
+            next.run(request).await
+        })
+    }
+}
@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// How requests are spread across the configured nodes
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeSelection {
+    /// Send everything to the first healthy node in configuration order
+    #[default]
+    PrimaryWithFallback,
+    /// Rotate through the healthy nodes, one request each
+    RoundRobin,
+}
+
+/// Health of one configured node, as last observed by the client
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct NodeStatus {
+    pub url: String,
+    pub healthy: bool,
+}
+
+struct Node {
+    url: String,
+    healthy: AtomicBool,
+}
+
+pub(crate) struct NodePool {
+    nodes: Vec<Node>,
+    selection: NodeSelection,
+    next: AtomicUsize,
+}
+
+impl NodePool {
+    pub(crate) fn new(urls: Vec<String>, selection: NodeSelection) -> Self {
+        NodePool {
+            nodes: urls
+                .into_iter()
+                .map(|url| Node {
+                    url,
+                    healthy: AtomicBool::new(true),
+                })
+                .collect(),
+            selection,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn url(&self, index: usize) -> &str {
+        &self.nodes[index].url
+    }
+
+    /// Node indexes in the order they should be tried for one request: healthy
+    /// nodes as ordered by the selection strategy, then unhealthy nodes as a
+    /// last resort.
+    pub(crate) fn candidates(&self) -> Vec<usize> {
+        let count = self.nodes.len();
+        let first = match self.selection {
+            NodeSelection::PrimaryWithFallback => 0,
+            NodeSelection::RoundRobin => self.next.fetch_add(1, Ordering::Relaxed) % count,
+        };
+        let (mut healthy, unhealthy): (Vec<usize>, Vec<usize>) = (0..count)
+            .map(|offset| (first + offset) % count)
+            .partition(|index| self.nodes[*index].healthy.load(Ordering::Relaxed));
+        healthy.extend(unhealthy);
+        healthy
+    }
+
+    pub(crate) fn mark_healthy(&self, index: usize) {
+        self.nodes[index].healthy.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true when the node was healthy until now, so exactly one caller
+    /// starts probing it.
+    pub(crate) fn mark_unhealthy(&self, index: usize) -> bool {
+        self.nodes[index].healthy.swap(false, Ordering::Relaxed)
+    }
+
+    pub(crate) fn is_healthy(&self, index: usize) -> bool {
+        self.nodes[index].healthy.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn status(&self) -> Vec<NodeStatus> {
+        self.nodes
+            .iter()
+            .map(|node| NodeStatus {
+                url: node.url.clone(),
+                healthy: node.healthy.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// The kind of work an operation asks the server to do
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationClass {
+    /// Searches, SQL `SELECT`s, describes, logs and job lookups
+    Read,
+    /// Record inserts, updates, upserts and deletes, including SQL DML
+    Write,
+    /// Schema, table, user, role, cluster and server management
+    Admin,
+}
+
+impl OperationClass {
+    /// Classify an operation body by its `operation` field. SQL is classified by
+    /// its leading keyword.
+    pub fn of(operation: &Value) -> Self {
+        let name = operation.get("operation").and_then(Value::as_str).unwrap_or_default();
+        match name {
+            "sql" => {
+                let sql = operation.get("sql").and_then(Value::as_str).unwrap_or_default();
+                let keyword = sql.split_whitespace().next().unwrap_or_default();
+                if keyword.eq_ignore_ascii_case("select") {
+                    OperationClass::Read
+                } else {
+                    OperationClass::Write
+                }
+            }
+            "search_by_hash" | "search_by_value" | "search_by_conditions" | "describe_all"
+            | "describe_schema" | "describe_table" | "read_log" | "read_audit_log"
+            | "read_transaction_log" | "get_job" | "search_jobs_by_start_date" | "user_info"
+            | "list_users" | "list_roles" | "system_information" | "cluster_status"
+            | "registration_info" | "get_fingerprint" | "get_configuration" | "get_components"
+            | "get_component_file" | "get_custom_functions" | "get_custom_function"
+            | "custom_functions_status" | "export_local" | "export_to_s3" => OperationClass::Read,
+            "insert" | "update" | "upsert" | "delete" | "csv_data_load" | "csv_file_load"
+            | "csv_url_load" | "import_from_s3" | "delete_files_before" | "delete_records_before"
+            | "delete_audit_logs_before" => OperationClass::Write,
+            _ => OperationClass::Admin,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OperationClass;
+
+    const READS: &[&str] = &[
+        "cluster_status",
+        "custom_functions_status",
+        "describe_all",
+        "describe_schema",
+        "describe_table",
+        "export_local",
+        "export_to_s3",
+        "get_component_file",
+        "get_components",
+        "get_configuration",
+        "get_custom_function",
+        "get_custom_functions",
+        "get_fingerprint",
+        "get_job",
+        "list_roles",
+        "list_users",
+        "read_audit_log",
+        "read_log",
+        "read_transaction_log",
+        "registration_info",
+        "search_by_hash",
+        "search_by_value",
+        "search_jobs_by_start_date",
+        "system_information",
+        "user_info",
+    ];
+
+    const WRITES: &[&str] = &[
+        "csv_data_load",
+        "csv_file_load",
+        "csv_url_load",
+        "delete",
+        "delete_audit_logs_before",
+        "delete_files_before",
+        "delete_records_before",
+        "import_from_s3",
+        "insert",
+        "update",
+    ];
+
+    const ADMIN: &[&str] = &[
+        "add_custom_function_project",
+        "add_node",
+        "add_role",
+        "add_user",
+        "alter_role",
+        "alter_user",
+        "create_authentication_tokens",
+        "create_schema",
+        "create_table",
+        "deploy_component",
+        "deploy_custom_function_project",
+        "drop_attribute",
+        "drop_component",
+        "drop_custom_function",
+        "drop_custom_function_project",
+        "drop_role",
+        "drop_schema",
+        "drop_table",
+        "drop_user",
+        "package_component",
+        "package_custom_function_project",
+        "refresh_operation_token",
+        "remove_node",
+        "restart",
+        "restart_service",
+        "set_component_file",
+        "set_custom_function",
+        "set_license",
+    ];
+
+    /// The names of the operations the client builds, from `"operation": "..."`
+    /// and `insert("operation", "...")` in its sources
+    fn sent_operations() -> Vec<String> {
+        let sources = [
+            include_str!("lib.rs"),
+            include_str!("batch.rs"),
+            include_str!("job.rs"),
+            include_str!("table.rs"),
+            include_str!("users.rs"),
+        ];
+        let mut names = Vec::new();
+        for source in sources.iter() {
+            for (index, _) in source.match_indices("\"operation\"") {
+                let rest = source[index + "\"operation\"".len()..].trim_start_matches([':', ',', ' ']);
+                if let Some(rest) = rest.strip_prefix('"') {
+                    let name = &rest[..rest.find('"').unwrap()];
+                    if name != "sql" && !names.iter().any(|known| known == name) {
+                        names.push(name.to_string());
+                    }
+                }
+            }
+        }
+        names
+    }
+
+    #[test]
+    fn every_sent_operation_is_classified() {
+        let operations = sent_operations();
+        assert!(operations.len() > 50, "found only {:?}", operations);
+        for name in operations {
+            let expected = if READS.contains(&name.as_str()) {
+                OperationClass::Read
+            } else if WRITES.contains(&name.as_str()) {
+                OperationClass::Write
+            } else {
+                assert!(ADMIN.contains(&name.as_str()), "{} is not in the expected classes", name);
+                OperationClass::Admin
+            };
+            assert_eq!(OperationClass::of(&json!({ "operation": &name })), expected, "{}", name);
+        }
+    }
+}
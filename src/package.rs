@@ -0,0 +1,59 @@
+use crate::error::Error;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Tar the contents of `dir` and encode the archive as base64, the payload
+/// format of `deploy_custom_function_project` and `deploy_component`
+///
+/// Entries are named relative to `dir` and added in name order, so the same
+/// files give the same payload. Every file is included, `node_modules` too;
+/// symlinks are followed, except those leading back to a directory already
+/// being packaged, which would loop forever.
+///
+/// # Examples
+///
+/// ```
+/// let payload = harperdb::package_directory("./dogs")?;
+/// ```
+pub fn package_directory<P: AsRef<Path>>(dir: P) -> Result<String, Error> {
+    let dir = dir.as_ref();
+    if !dir.is_dir() {
+        let message = format!("{} is not a directory", dir.display());
+        return Err(Error::Io(io::Error::new(io::ErrorKind::InvalidInput, message)));
+    }
+
+    let mut builder = tar::Builder::new(Vec::new());
+    let mut ancestors = vec![dir.canonicalize()?];
+    append_entries(&mut builder, dir, Path::new(""), &mut ancestors)?;
+    Ok(base64::encode(builder.into_inner()?))
+}
+
+/// `ancestors` holds the canonical paths of `dir` and the directories above it.
+fn append_entries(
+    builder: &mut tar::Builder<Vec<u8>>,
+    dir: &Path,
+    prefix: &Path,
+    ancestors: &mut Vec<PathBuf>,
+) -> Result<(), Error> {
+    let mut entries = fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let path = entry.path();
+        let name = prefix.join(entry.file_name());
+        if path.is_dir() {
+            let canonical = path.canonicalize()?;
+            if ancestors.contains(&canonical) {
+                continue;
+            }
+            builder.append_dir(&name, &path)?;
+            ancestors.push(canonical);
+            append_entries(builder, &path, &name, ancestors)?;
+            ancestors.pop();
+        } else {
+            builder.append_path_with_name(&path, &name)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,957 @@
+//! Builders for HarperDB SQL statements
+//!
+//! Identifiers are quoted with backticks and string values with single
+//! quotes, so names and values never change the shape of the statement.
+//! Statements render with `Display` and run through a client with `execute`,
+//! which rejects names containing a backtick.
+//!
+//! ```
+//! use harperdb::sql::{col, Select};
+//!
+//! let select = Select::from("dev", "dog")
+//!     .columns(["name", "age"])
+//!     .filter(col("age").gt(3))
+//!     .order_by("name")
+//!     .limit(10);
+//! assert_eq!(
+//!     select.to_string(),
+//!     "SELECT `name`, `age` FROM `dev`.`dog` WHERE `age` > 3 ORDER BY `name` LIMIT 10"
+//! );
+//! let dogs: Vec<Dog> = select.fetch(&harper_client).await?;
+//! ```
+
+use crate::error::Error;
+use crate::Harper;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::fmt;
+
+/// A column, optionally qualified by a table or alias: `"age"` or `"d.age"`
+pub fn col(name: &str) -> Expr {
+    Expr(Node::Column(name.split('.').map(str::to_string).collect()))
+}
+
+/// A value, quoted as needed. Values convert into `Expr` on their own where an
+/// expression is expected; `lit` is for the places a string would be taken as
+/// a column name.
+pub fn lit(value: impl Into<Expr>) -> Expr {
+    value.into()
+}
+
+/// `COUNT(*)`
+pub fn count_all() -> Expr {
+    Expr(Node::Function("COUNT", false, vec![Expr(Node::Star)]))
+}
+
+/// `COUNT(expr)`
+pub fn count(expr: impl Into<Column>) -> Expr {
+    function("COUNT", false, expr)
+}
+
+/// `COUNT(DISTINCT expr)`
+pub fn count_distinct(expr: impl Into<Column>) -> Expr {
+    function("COUNT", true, expr)
+}
+
+/// `SUM(expr)`
+pub fn sum(expr: impl Into<Column>) -> Expr {
+    function("SUM", false, expr)
+}
+
+/// `AVG(expr)`
+pub fn avg(expr: impl Into<Column>) -> Expr {
+    function("AVG", false, expr)
+}
+
+/// `MIN(expr)`
+pub fn min(expr: impl Into<Column>) -> Expr {
+    function("MIN", false, expr)
+}
+
+/// `MAX(expr)`
+pub fn max(expr: impl Into<Column>) -> Expr {
+    function("MAX", false, expr)
+}
+
+fn function(name: &'static str, distinct: bool, expr: impl Into<Column>) -> Expr {
+    Expr(Node::Function(name, distinct, vec![expr.into().expr]))
+}
+
+/// An SQL expression: a column, a value, a comparison, a condition or an
+/// aggregate
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr(Node);
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Column(Vec<String>),
+    Star,
+    Literal(Value),
+    Compare(Box<Expr>, &'static str, Box<Expr>),
+    Logical(Box<Expr>, &'static str, Box<Expr>),
+    Not(Box<Expr>),
+    IsNull(Box<Expr>, bool),
+    In(Box<Expr>, Vec<Expr>),
+    Between(Box<Expr>, Box<Expr>, Box<Expr>),
+    Function(&'static str, bool, Vec<Expr>),
+}
+
+impl Expr {
+    fn compare(self, operator: &'static str, other: impl Into<Expr>) -> Expr {
+        Expr(Node::Compare(Box::new(self), operator, Box::new(other.into())))
+    }
+
+    pub fn eq(self, other: impl Into<Expr>) -> Expr {
+        self.compare("=", other)
+    }
+
+    pub fn ne(self, other: impl Into<Expr>) -> Expr {
+        self.compare("<>", other)
+    }
+
+    pub fn gt(self, other: impl Into<Expr>) -> Expr {
+        self.compare(">", other)
+    }
+
+    pub fn ge(self, other: impl Into<Expr>) -> Expr {
+        self.compare(">=", other)
+    }
+
+    pub fn lt(self, other: impl Into<Expr>) -> Expr {
+        self.compare("<", other)
+    }
+
+    pub fn le(self, other: impl Into<Expr>) -> Expr {
+        self.compare("<=", other)
+    }
+
+    /// `LIKE`, with `%` and `_` wildcards
+    pub fn like(self, pattern: impl Into<Expr>) -> Expr {
+        self.compare("LIKE", pattern)
+    }
+
+    pub fn is_null(self) -> Expr {
+        Expr(Node::IsNull(Box::new(self), true))
+    }
+
+    pub fn is_not_null(self) -> Expr {
+        Expr(Node::IsNull(Box::new(self), false))
+    }
+
+    pub fn in_list<I>(self, values: I) -> Expr
+    where
+        I: IntoIterator,
+        I::Item: Into<Expr>,
+    {
+        Expr(Node::In(Box::new(self), values.into_iter().map(Into::into).collect()))
+    }
+
+    pub fn between(self, low: impl Into<Expr>, high: impl Into<Expr>) -> Expr {
+        Expr(Node::Between(Box::new(self), Box::new(low.into()), Box::new(high.into())))
+    }
+
+    pub fn and(self, other: Expr) -> Expr {
+        Expr(Node::Logical(Box::new(self), "AND", Box::new(other)))
+    }
+
+    pub fn or(self, other: Expr) -> Expr {
+        Expr(Node::Logical(Box::new(self), "OR", Box::new(other)))
+    }
+
+    /// Select the expression under another name: `COUNT(*) AS total`
+    pub fn alias(self, alias: &str) -> Column {
+        Column {
+            expr: self,
+            alias: Some(alias.to_string()),
+        }
+    }
+
+    /// Reject what renders but cannot run, like `IN ()`
+    fn check(&self) -> Result<(), Error> {
+        match &self.0 {
+            Node::Column(path) => path.iter().try_for_each(|part| check_identifier(part)),
+            Node::Star | Node::Literal(_) => Ok(()),
+            Node::Compare(left, _, right) | Node::Logical(left, _, right) => {
+                left.check()?;
+                right.check()
+            }
+            Node::Not(expr) | Node::IsNull(expr, _) => expr.check(),
+            Node::In(_, values) if values.is_empty() => Err(Error::Sql("IN needs at least one value".to_string())),
+            Node::In(expr, values) => {
+                expr.check()?;
+                values.iter().try_for_each(Expr::check)
+            }
+            Node::Between(expr, low, high) => {
+                expr.check()?;
+                low.check()?;
+                high.check()
+            }
+            Node::Function(_, _, args) => args.iter().try_for_each(Expr::check),
+        }
+    }
+
+    // Conditions nested in a comparison or in a different logical operator
+    // get parentheses, and so do the operands of `IN`, `BETWEEN` and `IS NULL`
+    // (with an empty `parent`) unless they are a single value.
+    fn fmt_operand(&self, f: &mut fmt::Formatter<'_>, parent: &str) -> fmt::Result {
+        let parenthesize = match &self.0 {
+            Node::Logical(_, operator, _) => *operator != parent,
+            Node::Compare(..) | Node::Not(_) | Node::IsNull(..) | Node::In(..) | Node::Between(..) => {
+                parent != "AND" && parent != "OR"
+            }
+            Node::Column(_) | Node::Star | Node::Literal(_) | Node::Function(..) => false,
+        };
+        if parenthesize {
+            write!(f, "({})", self)
+        } else {
+            write!(f, "{}", self)
+        }
+    }
+}
+
+impl std::ops::Not for Expr {
+    type Output = Expr;
+
+    fn not(self) -> Expr {
+        Expr(Node::Not(Box::new(self)))
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Node::Column(path) => {
+                for (index, part) in path.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(".")?;
+                    }
+                    write_identifier(f, part)?;
+                }
+                Ok(())
+            }
+            Node::Star => f.write_str("*"),
+            Node::Literal(value) => write_literal(f, value),
+            Node::Compare(left, operator, right) => {
+                left.fmt_operand(f, operator)?;
+                write!(f, " {} ", operator)?;
+                right.fmt_operand(f, operator)
+            }
+            Node::Logical(left, operator, right) => {
+                left.fmt_operand(f, operator)?;
+                write!(f, " {} ", operator)?;
+                right.fmt_operand(f, operator)
+            }
+            Node::Not(expr) => write!(f, "NOT ({})", expr),
+            Node::IsNull(expr, null) => {
+                expr.fmt_operand(f, "")?;
+                f.write_str(if *null { " IS NULL" } else { " IS NOT NULL" })
+            }
+            Node::In(expr, values) => {
+                expr.fmt_operand(f, "")?;
+                f.write_str(" IN (")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    value.fmt_operand(f, "")?;
+                }
+                f.write_str(")")
+            }
+            Node::Between(expr, low, high) => {
+                expr.fmt_operand(f, "")?;
+                f.write_str(" BETWEEN ")?;
+                low.fmt_operand(f, "")?;
+                f.write_str(" AND ")?;
+                high.fmt_operand(f, "")
+            }
+            Node::Function(name, distinct, args) => {
+                write!(f, "{}(", name)?;
+                if *distinct {
+                    f.write_str("DISTINCT ")?;
+                }
+                write_list(f, args)?;
+                f.write_str(")")
+            }
+        }
+    }
+}
+
+macro_rules! literal_from {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Expr {
+                fn from(value: $t) -> Self {
+                    Expr(Node::Literal(Value::from(value)))
+                }
+            }
+        )*
+    };
+}
+
+literal_from!(i32, i64, u32, u64, f64, bool, &str, String);
+
+impl From<Value> for Expr {
+    fn from(value: Value) -> Self {
+        Expr(Node::Literal(value))
+    }
+}
+
+/// A selected, grouped or ordered expression. Strings are column names.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    expr: Expr,
+    alias: Option<String>,
+}
+
+impl From<&str> for Column {
+    fn from(name: &str) -> Self {
+        let expr = if name == "*" { Expr(Node::Star) } else { col(name) };
+        Column { expr, alias: None }
+    }
+}
+
+impl From<String> for Column {
+    fn from(name: String) -> Self {
+        Column::from(name.as_str())
+    }
+}
+
+impl From<Expr> for Column {
+    fn from(expr: Expr) -> Self {
+        Column { expr, alias: None }
+    }
+}
+
+impl Column {
+    fn check(&self) -> Result<(), Error> {
+        self.expr.check()?;
+        self.alias.iter().try_for_each(|alias| check_identifier(alias))
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.expr)?;
+        if let Some(alias) = &self.alias {
+            f.write_str(" AS ")?;
+            write_identifier(f, alias)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct TableRef {
+    schema: String,
+    table: String,
+    alias: Option<String>,
+}
+
+impl TableRef {
+    fn new(schema: &str, table: &str) -> Self {
+        TableRef {
+            schema: schema.to_string(),
+            table: table.to_string(),
+            alias: None,
+        }
+    }
+
+    fn check(&self) -> Result<(), Error> {
+        check_identifier(&self.schema)?;
+        check_identifier(&self.table)?;
+        self.alias.iter().try_for_each(|alias| check_identifier(alias))
+    }
+}
+
+impl fmt::Display for TableRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.schema.is_empty() {
+            write_identifier(f, &self.schema)?;
+            f.write_str(".")?;
+        }
+        write_identifier(f, &self.table)?;
+        if let Some(alias) = &self.alias {
+            f.write_str(" AS ")?;
+            write_identifier(f, alias)?;
+        }
+        Ok(())
+    }
+}
+
+/// A table joined into a `Select`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Join {
+    kind: &'static str,
+    table: TableRef,
+    on: Option<Expr>,
+}
+
+impl Join {
+    fn new(kind: &'static str, schema: &str, table: &str) -> Self {
+        Join {
+            kind,
+            table: TableRef::new(schema, table),
+            on: None,
+        }
+    }
+
+    pub fn inner(schema: &str, table: &str) -> Self {
+        Join::new("INNER JOIN", schema, table)
+    }
+
+    pub fn left(schema: &str, table: &str) -> Self {
+        Join::new("LEFT JOIN", schema, table)
+    }
+
+    pub fn right(schema: &str, table: &str) -> Self {
+        Join::new("RIGHT JOIN", schema, table)
+    }
+
+    pub fn full(schema: &str, table: &str) -> Self {
+        Join::new("FULL OUTER JOIN", schema, table)
+    }
+
+    pub fn alias(mut self, alias: &str) -> Self {
+        self.table.alias = Some(alias.to_string());
+        self
+    }
+
+    pub fn on(mut self, condition: Expr) -> Self {
+        self.on = Some(condition);
+        self
+    }
+}
+
+impl fmt::Display for Join {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.kind, self.table)?;
+        if let Some(on) = &self.on {
+            write!(f, " ON {}", on)?;
+        }
+        Ok(())
+    }
+}
+
+/// A `SELECT` statement
+#[derive(Debug, Clone, PartialEq)]
+pub struct Select {
+    distinct: bool,
+    columns: Vec<Column>,
+    from: TableRef,
+    joins: Vec<Join>,
+    filter: Option<Expr>,
+    group_by: Vec<Column>,
+    having: Option<Expr>,
+    order_by: Vec<(Column, bool)>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+}
+
+impl Select {
+    /// Select every column of `schema.table`; an empty schema is left out
+    pub fn from(schema: &str, table: &str) -> Self {
+        Select {
+            distinct: false,
+            columns: Vec::new(),
+            from: TableRef::new(schema, table),
+            joins: Vec::new(),
+            filter: None,
+            group_by: Vec::new(),
+            having: None,
+            order_by: Vec::new(),
+            limit: None,
+            offset: None,
+        }
+    }
+
+    /// Name the table for qualified columns and joins: `FROM dev.dog AS d`
+    pub fn alias(mut self, alias: &str) -> Self {
+        self.from.alias = Some(alias.to_string());
+        self
+    }
+
+    /// Add selected columns or expressions; `*` until one is added
+    pub fn columns<I>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Column>,
+    {
+        self.columns.extend(columns.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn distinct(mut self) -> Self {
+        self.distinct = true;
+        self
+    }
+
+    pub fn join(mut self, join: Join) -> Self {
+        self.joins.push(join);
+        self
+    }
+
+    /// Add a `WHERE` condition; several are combined with `AND`
+    pub fn filter(mut self, condition: Expr) -> Self {
+        self.filter = Some(and(self.filter.take(), condition));
+        self
+    }
+
+    pub fn group_by<I>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Column>,
+    {
+        self.group_by.extend(columns.into_iter().map(Into::into));
+        self
+    }
+
+    /// Add a `HAVING` condition; several are combined with `AND`
+    pub fn having(mut self, condition: Expr) -> Self {
+        self.having = Some(and(self.having.take(), condition));
+        self
+    }
+
+    pub fn order_by(mut self, column: impl Into<Column>) -> Self {
+        self.order_by.push((column.into(), false));
+        self
+    }
+
+    pub fn order_by_desc(mut self, column: impl Into<Column>) -> Self {
+        self.order_by.push((column.into(), true));
+        self
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Run the statement with `Harper::query`
+    pub async fn execute(&self, harper: &Harper) -> Result<reqwest::Response, Error> {
+        self.check()?;
+        harper.query(&self.to_string()).await
+    }
+
+    /// Run the statement and decode the rows
+    pub async fn fetch<T: DeserializeOwned>(&self, harper: &Harper) -> Result<Vec<T>, Error> {
+        let res = Error::check(self.execute(harper).await?).await?;
+        Ok(res.json().await?)
+    }
+}
+
+impl Select {
+    fn check(&self) -> Result<(), Error> {
+        self.from.check()?;
+        for join in &self.joins {
+            join.table.check()?;
+            join.on.iter().try_for_each(Expr::check)?;
+        }
+        self.filter.iter().chain(&self.having).try_for_each(Expr::check)?;
+        let order_by = self.order_by.iter().map(|(column, _)| column);
+        self.columns.iter().chain(&self.group_by).chain(order_by).try_for_each(Column::check)
+    }
+}
+
+impl fmt::Display for Select {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SELECT ")?;
+        if self.distinct {
+            f.write_str("DISTINCT ")?;
+        }
+        if self.columns.is_empty() {
+            f.write_str("*")?;
+        } else {
+            write_list(f, &self.columns)?;
+        }
+        write!(f, " FROM {}", self.from)?;
+        for join in &self.joins {
+            write!(f, " {}", join)?;
+        }
+        if let Some(filter) = &self.filter {
+            write!(f, " WHERE {}", filter)?;
+        }
+        if !self.group_by.is_empty() {
+            f.write_str(" GROUP BY ")?;
+            let keys: Vec<&Expr> = self.group_by.iter().map(|column| &column.expr).collect();
+            write_list(f, &keys)?;
+        }
+        if let Some(having) = &self.having {
+            write!(f, " HAVING {}", having)?;
+        }
+        for (index, (column, descending)) in self.order_by.iter().enumerate() {
+            f.write_str(if index == 0 { " ORDER BY " } else { ", " })?;
+            write!(f, "{}", column.expr)?;
+            if *descending {
+                f.write_str(" DESC")?;
+            }
+        }
+        if let Some(limit) = self.limit {
+            write!(f, " LIMIT {}", limit)?;
+        }
+        if let Some(offset) = self.offset {
+            write!(f, " OFFSET {}", offset)?;
+        }
+        Ok(())
+    }
+}
+
+/// An `INSERT` statement
+///
+/// The columns are the attributes of all records, in the order records add
+/// them and sorted within a record; attributes a record lacks are inserted as
+/// `NULL`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Insert {
+    table: TableRef,
+    columns: Vec<String>,
+    records: Vec<Map<String, Value>>,
+}
+
+impl Insert {
+    pub fn into(schema: &str, table: &str) -> Self {
+        Insert {
+            table: TableRef::new(schema, table),
+            columns: Vec::new(),
+            records: Vec::new(),
+        }
+    }
+
+    /// Add a record; values other than JSON objects are ignored
+    pub fn record(mut self, record: Value) -> Self {
+        if let Value::Object(record) = record {
+            let mut added: Vec<&String> = record.keys().filter(|key| !self.columns.contains(key)).collect();
+            added.sort();
+            self.columns.extend(added.into_iter().cloned());
+            self.records.push(record);
+        }
+        self
+    }
+
+    /// Run the statement with `Harper::query`
+    pub async fn execute(&self, harper: &Harper) -> Result<reqwest::Response, Error> {
+        self.check()?;
+        harper.query(&self.to_string()).await
+    }
+
+    fn check(&self) -> Result<(), Error> {
+        if self.columns.is_empty() {
+            return Err(Error::Sql("INSERT needs at least one record with an attribute".to_string()));
+        }
+        self.table.check()?;
+        self.columns.iter().try_for_each(|column| check_identifier(column))
+    }
+}
+
+impl fmt::Display for Insert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "INSERT INTO {} (", self.table)?;
+        for (index, column) in self.columns.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            write_identifier(f, column)?;
+        }
+        f.write_str(") VALUES ")?;
+        for (index, record) in self.records.iter().enumerate() {
+            f.write_str(if index == 0 { "(" } else { ", (" })?;
+            for (index, column) in self.columns.iter().enumerate() {
+                if index > 0 {
+                    f.write_str(", ")?;
+                }
+                write_literal(f, record.get(column).unwrap_or(&Value::Null))?;
+            }
+            f.write_str(")")?;
+        }
+        Ok(())
+    }
+}
+
+/// An `UPDATE` statement
+#[derive(Debug, Clone, PartialEq)]
+pub struct Update {
+    table: TableRef,
+    assignments: Vec<(String, Expr)>,
+    filter: Option<Expr>,
+}
+
+impl Update {
+    pub fn table(schema: &str, table: &str) -> Self {
+        Update {
+            table: TableRef::new(schema, table),
+            assignments: Vec::new(),
+            filter: None,
+        }
+    }
+
+    /// Set a column to a value or expression
+    pub fn set(mut self, column: &str, value: impl Into<Expr>) -> Self {
+        self.assignments.push((column.to_string(), value.into()));
+        self
+    }
+
+    /// Add a `WHERE` condition; several are combined with `AND`. Without one,
+    /// every record is updated.
+    pub fn filter(mut self, condition: Expr) -> Self {
+        self.filter = Some(and(self.filter.take(), condition));
+        self
+    }
+
+    /// Run the statement with `Harper::query`
+    pub async fn execute(&self, harper: &Harper) -> Result<reqwest::Response, Error> {
+        self.check()?;
+        harper.query(&self.to_string()).await
+    }
+
+    fn check(&self) -> Result<(), Error> {
+        self.table.check()?;
+        for (column, value) in &self.assignments {
+            check_identifier(column)?;
+            value.check()?;
+        }
+        self.filter.iter().try_for_each(Expr::check)
+    }
+}
+
+impl fmt::Display for Update {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "UPDATE {} SET ", self.table)?;
+        for (index, (column, value)) in self.assignments.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            write_identifier(f, column)?;
+            write!(f, " = {}", value)?;
+        }
+        if let Some(filter) = &self.filter {
+            write!(f, " WHERE {}", filter)?;
+        }
+        Ok(())
+    }
+}
+
+/// A `DELETE` statement
+#[derive(Debug, Clone, PartialEq)]
+pub struct Delete {
+    table: TableRef,
+    filter: Option<Expr>,
+}
+
+impl Delete {
+    pub fn from(schema: &str, table: &str) -> Self {
+        Delete {
+            table: TableRef::new(schema, table),
+            filter: None,
+        }
+    }
+
+    /// Add a `WHERE` condition; several are combined with `AND`. Without one,
+    /// every record is deleted.
+    pub fn filter(mut self, condition: Expr) -> Self {
+        self.filter = Some(and(self.filter.take(), condition));
+        self
+    }
+
+    /// Run the statement with `Harper::query`
+    pub async fn execute(&self, harper: &Harper) -> Result<reqwest::Response, Error> {
+        self.check()?;
+        harper.query(&self.to_string()).await
+    }
+
+    fn check(&self) -> Result<(), Error> {
+        self.table.check()?;
+        self.filter.iter().try_for_each(Expr::check)
+    }
+}
+
+impl fmt::Display for Delete {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DELETE FROM {}", self.table)?;
+        if let Some(filter) = &self.filter {
+            write!(f, " WHERE {}", filter)?;
+        }
+        Ok(())
+    }
+}
+
+fn and(existing: Option<Expr>, condition: Expr) -> Expr {
+    match existing {
+        Some(existing) => existing.and(condition),
+        None => condition,
+    }
+}
+
+fn write_list<T: fmt::Display>(f: &mut fmt::Formatter<'_>, items: &[T]) -> fmt::Result {
+    for (index, item) in items.iter().enumerate() {
+        if index > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+fn write_identifier(f: &mut fmt::Formatter<'_>, name: &str) -> fmt::Result {
+    write!(f, "`{}`", name.replace('`', "``"))
+}
+
+/// HarperDB's SQL parser has no escape for a backtick inside a quoted name.
+fn check_identifier(name: &str) -> Result<(), Error> {
+    if name.contains('`') {
+        return Err(Error::Sql(format!("the name {} contains a backtick", name)));
+    }
+    Ok(())
+}
+
+/// Strings are single-quoted with backslashes escaped and quotes doubled, as
+/// HarperDB's SQL parser reads a backslash as an escape; arrays and objects
+/// are written as a string of their JSON, quoted the same way.
+fn write_literal(f: &mut fmt::Formatter<'_>, value: &Value) -> fmt::Result {
+    let quote = |f: &mut fmt::Formatter<'_>, text: &str| write!(f, "'{}'", text.replace('\\', "\\\\").replace('\'', "''"));
+    match value {
+        Value::Null => f.write_str("NULL"),
+        Value::Bool(value) => write!(f, "{}", value),
+        Value::Number(value) => write!(f, "{}", value),
+        Value::String(value) => quote(f, value),
+        value => quote(f, &value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn select() {
+        let select = Select::from("dev", "dog")
+            .columns(["name", "age"])
+            .filter(col("age").gt(3))
+            .order_by("name")
+            .limit(10);
+        assert_eq!(
+            select.to_string(),
+            "SELECT `name`, `age` FROM `dev`.`dog` WHERE `age` > 3 ORDER BY `name` LIMIT 10"
+        );
+
+        let select = Select::from("dev", "dog")
+            .alias("d")
+            .columns(vec![col("b.name").alias("breed"), count_all().alias("dogs"), avg("d.age").alias("age")])
+            .join(Join::inner("dev", "breed").alias("b").on(col("d.breed_id").eq(col("b.id"))))
+            .filter(col("d.name").like("P%").or(col("d.name").eq("Kato")))
+            .filter(!col("d.adorable").eq(false))
+            .group_by(["b.name"])
+            .having(count_all().gt(1))
+            .order_by_desc(count_all());
+        assert_eq!(
+            select.to_string(),
+            "SELECT `b`.`name` AS `breed`, COUNT(*) AS `dogs`, AVG(`d`.`age`) AS `age` \
+             FROM `dev`.`dog` AS `d` INNER JOIN `dev`.`breed` AS `b` ON `d`.`breed_id` = `b`.`id` \
+             WHERE (`d`.`name` LIKE 'P%' OR `d`.`name` = 'Kato') AND NOT (`d`.`adorable` = false) \
+             GROUP BY `b`.`name` HAVING COUNT(*) > 1 ORDER BY COUNT(*) DESC"
+        );
+
+        let full = Select::from("dev", "dog").join(Join::full("dev", "breed").on(col("dog.breed_id").eq(col("breed.id"))));
+        assert_eq!(
+            full.to_string(),
+            "SELECT * FROM `dev`.`dog` FULL OUTER JOIN `dev`.`breed` ON `dog`.`breed_id` = `breed`.`id`"
+        );
+    }
+
+    #[test]
+    fn compound_operands() {
+        let either = || col("a").eq(1).or(col("b").eq(2));
+        assert_eq!(either().is_null().to_string(), "(`a` = 1 OR `b` = 2) IS NULL");
+        assert_eq!((!col("a").eq(1)).is_not_null().to_string(), "(NOT (`a` = 1)) IS NOT NULL");
+        assert_eq!(either().in_list(vec![true]).to_string(), "(`a` = 1 OR `b` = 2) IN (true)");
+        assert_eq!(col("a").in_list(vec![col("b").eq(1)]).to_string(), "`a` IN ((`b` = 1))");
+        assert_eq!(
+            col("a").between(col("b").eq(1), either()).to_string(),
+            "`a` BETWEEN (`b` = 1) AND (`a` = 1 OR `b` = 2)"
+        );
+        assert_eq!(col("a").is_null().eq(false).to_string(), "(`a` IS NULL) = false");
+        assert_eq!(
+            col("a").is_null().and(col("b").between(1, 2)).to_string(),
+            "`a` IS NULL AND `b` BETWEEN 1 AND 2"
+        );
+        assert_eq!(count_all().between(1, 3).to_string(), "COUNT(*) BETWEEN 1 AND 3");
+    }
+
+    #[test]
+    fn quoting() {
+        let quoted = Select::from("dev", "odd`name")
+            .columns([lit("it's")])
+            .filter(col("owner").in_list(vec!["O'Brien", "x'; DROP TABLE dog; --"]));
+        assert_eq!(
+            quoted.to_string(),
+            "SELECT 'it''s' FROM `dev`.`odd``name` WHERE `owner` IN ('O''Brien', 'x''; DROP TABLE dog; --')"
+        );
+        assert!(matches!(quoted.check(), Err(Error::Sql(_))));
+
+        // A backslash must not escape the quote that follows it.
+        let escaped = Select::from("dev", "dog").filter(col("name").eq(r"x\' OR 1=1 --"));
+        assert_eq!(escaped.to_string(), r"SELECT * FROM `dev`.`dog` WHERE `name` = 'x\\'' OR 1=1 --'");
+        let trailing = Select::from("dev", "dog").filter(col("path").eq(r"C:\dogs\"));
+        assert_eq!(trailing.to_string(), r"SELECT * FROM `dev`.`dog` WHERE `path` = 'C:\\dogs\\'");
+        let nested = Insert::into("dev", "dog").record(json!({ "id": 1, "owner": { "name": r"O\'Brien\" } }));
+        assert_eq!(
+            nested.to_string(),
+            r#"INSERT INTO `dev`.`dog` (`id`, `owner`) VALUES (1, '{"name":"O\\\\''Brien\\\\"}')"#
+        );
+    }
+
+    #[test]
+    fn insert() {
+        let insert = Insert::into("dev", "dog")
+            .record(json!({ "id": 1, "name": "Penny", "age": 5 }))
+            .record(json!({ "id": 2, "name": "Kato" }));
+        assert_eq!(
+            insert.to_string(),
+            "INSERT INTO `dev`.`dog` (`age`, `id`, `name`) VALUES (5, 1, 'Penny'), (NULL, 2, 'Kato')"
+        );
+
+        let nested = Insert::into("dev", "dog")
+            .record(json!({ "id": 3, "owner": { "name": "O'Brien" } }))
+            .record(json!({ "id": 4, "tags": ["it's"] }));
+        assert_eq!(
+            nested.to_string(),
+            r#"INSERT INTO `dev`.`dog` (`id`, `owner`, `tags`) VALUES (3, '{"name":"O''Brien"}', NULL), (4, NULL, '["it''s"]')"#
+        );
+    }
+
+    #[test]
+    fn update_and_delete() {
+        let update = Update::table("dev", "dog").set("age", 6).set("name", "Penny").filter(col("id").eq(1));
+        assert_eq!(update.to_string(), "UPDATE `dev`.`dog` SET `age` = 6, `name` = 'Penny' WHERE `id` = 1");
+
+        let delete = Delete::from("dev", "dog").filter(col("age").between(1, 3)).filter(col("name").is_null());
+        assert_eq!(delete.to_string(), "DELETE FROM `dev`.`dog` WHERE `age` BETWEEN 1 AND 3 AND `name` IS NULL");
+    }
+
+    #[test]
+    fn rejects_what_cannot_run() {
+        let empty_in = || col("id").in_list(Vec::<i64>::new());
+        assert!(matches!(Select::from("dev", "dog").filter(empty_in()).check(), Err(Error::Sql(_))));
+        let nested = Delete::from("dev", "dog").filter(col("age").gt(3).and(!empty_in()));
+        assert!(matches!(nested.check(), Err(Error::Sql(_))));
+
+        assert!(matches!(Insert::into("dev", "dog").check(), Err(Error::Sql(_))));
+        assert!(matches!(Insert::into("dev", "dog").record(json!({})).check(), Err(Error::Sql(_))));
+
+        let names = [
+            Select::from("dev", "dog").columns(["na`me"]).check(),
+            Select::from("dev", "dog").columns(vec![count_all().alias("a`b")]).check(),
+            Select::from("dev", "dog").join(Join::left("dev", "b`reed")).check(),
+            Update::table("dev", "dog").set("a`ge", 1).check(),
+            Insert::into("d`ev", "dog").record(json!({ "id": 1 })).check(),
+            Insert::into("dev", "dog").record(json!({ "i`d": 1 })).check(),
+        ];
+        assert!(names.iter().all(|result| matches!(result, Err(Error::Sql(_)))));
+
+        assert!(Select::from("dev", "dog").filter(col("id").in_list(vec![1])).check().is_ok());
+        assert!(Insert::into("dev", "dog").record(json!({ "id": 1 })).check().is_ok());
+    }
+}
@@ -0,0 +1,243 @@
+use crate::error::Error;
+use crate::Harper;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// A schema of a HarperDB instance, from `Harper::schema`
+#[derive(Clone)]
+pub struct Schema {
+    harper: Harper,
+    name: String,
+}
+
+/// A table of a HarperDB instance, from `Harper::table` or `Schema::table`
+///
+/// Operations are sent through the client it came from, with the schema and
+/// table filled in. When the schema name is empty, it is left out of the
+/// operations and the server picks its default.
+#[derive(Clone)]
+pub struct Table {
+    harper: Harper,
+    schema: String,
+    table: String,
+}
+
+impl Schema {
+    pub(crate) fn new(harper: Harper, name: String) -> Self {
+        Schema { harper, name }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn table(&self, table: impl Into<String>) -> Table {
+        Table::new(self.harper.clone(), self.name.clone(), table.into())
+    }
+
+    /// Describe Schema
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let result = harper_client.schema("dev").describe().await?;
+    /// ```
+    pub async fn describe(&self) -> Result<reqwest::Response, Error> {
+        let map = json!({
+            "operation": "describe_schema",
+            "schema": &self.name,
+        });
+
+        let res = self.harper.request(&map).await?;
+
+        Ok(res)
+    }
+}
+
+impl Table {
+    pub(crate) fn new(harper: Harper, schema: String, table: String) -> Self {
+        Table { harper, schema, table }
+    }
+
+    pub fn schema(&self) -> &str {
+        &self.schema
+    }
+
+    pub fn name(&self) -> &str {
+        &self.table
+    }
+
+    // The operation body with `schema` and `table` filled in.
+    fn operation(&self, operation: &str) -> Map<String, Value> {
+        let mut map = Map::new();
+        map.insert("operation".to_string(), json!(operation));
+        if !self.schema.is_empty() {
+            map.insert("schema".to_string(), json!(&self.schema));
+        }
+        map.insert("table".to_string(), json!(&self.table));
+        map
+    }
+
+    /// Insert Records
+    ///
+    /// # Arguments
+    ///
+    /// * `records`  (required) - Value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let result = dog.insert(json!([{ "id": 1, "name": "Penny" }])).await?;
+    /// ```
+    pub async fn insert(&self, records: Value) -> Result<reqwest::Response, Error> {
+        let mut map = self.operation("insert");
+        map.insert("records".to_string(), records);
+
+        let res = self.harper.request(&map).await?;
+
+        Ok(res)
+    }
+
+    /// Update Records
+    ///
+    /// # Arguments
+    ///
+    /// * `records`  (required) - Value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let result = dog.update(json!([{ "id": 1, "age": 5 }])).await?;
+    /// ```
+    pub async fn update(&self, records: Value) -> Result<reqwest::Response, Error> {
+        let mut map = self.operation("update");
+        map.insert("records".to_string(), records);
+
+        let res = self.harper.request(&map).await?;
+
+        Ok(res)
+    }
+
+    /// Upsert Records
+    ///
+    /// Inserts the records that do not exist yet and updates the others.
+    ///
+    /// # Arguments
+    ///
+    /// * `records`  (required) - Value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let result = dog.upsert(json!([{ "id": 1, "age": 6 }, { "id": 2, "name": "Kato" }])).await?;
+    /// ```
+    pub async fn upsert(&self, records: Value) -> Result<reqwest::Response, Error> {
+        let mut map = self.operation("upsert");
+        map.insert("records".to_string(), records);
+
+        let res = self.harper.request(&map).await?;
+
+        Ok(res)
+    }
+
+    /// Delete Records
+    ///
+    /// # Arguments
+    ///
+    /// * `hash_values`  (required) - &[H], strings or numbers
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let result = dog.delete(&[1, 2]).await?;
+    /// ```
+    pub async fn delete<H: Serialize>(&self, hash_values: &[H]) -> Result<reqwest::Response, Error> {
+        let mut map = self.operation("delete");
+        map.insert("hash_values".to_string(), serde_json::to_value(hash_values)?);
+
+        let res = self.harper.request(&map).await?;
+
+        Ok(res)
+    }
+
+    /// Get Records by hash, with `search_by_hash`
+    ///
+    /// # Arguments
+    ///
+    /// * `hash_values`  (required) - &[H], strings or numbers
+    /// * `get_attributes`  (required) - &[&str], `["*"]` for all
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let result = dog.get(&[1], &["name", "age"]).await?;
+    /// ```
+    pub async fn get<H: Serialize>(&self, hash_values: &[H], get_attributes: &[&str]) -> Result<reqwest::Response, Error> {
+        let mut map = self.operation("search_by_hash");
+        map.insert("hash_values".to_string(), serde_json::to_value(hash_values)?);
+        map.insert("get_attributes".to_string(), json!(get_attributes));
+
+        let res = self.harper.request(&map).await?;
+
+        Ok(res)
+    }
+
+    /// Search By Value
+    ///
+    /// # Arguments
+    ///
+    /// * `search_attribute`  (required) - &str
+    /// * `search_value`  (required) - impl Serialize, may use `*` wildcards
+    /// * `get_attributes`  (required) - &[&str], `["*"]` for all
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let result = dog.search_by_value("name", "Pen*", &["*"]).await?;
+    /// ```
+    pub async fn search_by_value<V: Serialize>(
+        &self,
+        search_attribute: &str,
+        search_value: V,
+        get_attributes: &[&str],
+    ) -> Result<reqwest::Response, Error> {
+        let mut map = self.operation("search_by_value");
+        map.insert("search_attribute".to_string(), json!(search_attribute));
+        map.insert("search_value".to_string(), serde_json::to_value(search_value)?);
+        map.insert("get_attributes".to_string(), json!(get_attributes));
+
+        let res = self.harper.request(&map).await?;
+
+        Ok(res)
+    }
+
+    /// Describe Table
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let result = dog.describe().await?;
+    /// ```
+    pub async fn describe(&self) -> Result<reqwest::Response, Error> {
+        let map = self.operation("describe_table");
+
+        let res = self.harper.request(&map).await?;
+
+        Ok(res)
+    }
+
+    /// Drop Table
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let result = dog.drop().await?;
+    /// ```
+    pub async fn drop(&self) -> Result<reqwest::Response, Error> {
+        let map = self.operation("drop_table");
+
+        let res = self.harper.request(&map).await?;
+
+        Ok(res)
+    }
+}
@@ -0,0 +1,279 @@
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A point in time as HarperDB stores it: milliseconds since the Unix epoch (UTC).
+///
+/// Serializes as an ISO 8601 string (`2021-01-25T22:05:27.464Z`), which is what the
+/// operations API expects for date parameters, and deserializes from either an
+/// ISO 8601 string or an epoch-millisecond number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp(i64);
+
+impl Timestamp {
+    /// Create a Timestamp from milliseconds since the Unix epoch
+    pub fn from_millis(millis: i64) -> Self {
+        Timestamp(millis)
+    }
+
+    /// The current time
+    pub fn now() -> Self {
+        Timestamp::from(SystemTime::now())
+    }
+
+    /// Milliseconds since the Unix epoch
+    pub fn as_millis(&self) -> i64 {
+        self.0
+    }
+
+    /// Format as an ISO 8601 date (`2021-01-25`)
+    pub fn to_date_string(&self) -> String {
+        let (year, month, day) = civil_from_days(self.0.div_euclid(MILLIS_PER_DAY));
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    }
+
+    /// Convert to a `chrono::DateTime`; `None` when out of its range
+    #[cfg(feature = "chrono")]
+    pub fn to_chrono(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        use chrono::TimeZone;
+        chrono::Utc.timestamp_millis_opt(self.0).single()
+    }
+
+    /// Convert to a `time::OffsetDateTime` in UTC; `None` when out of its range
+    #[cfg(feature = "time")]
+    pub fn to_offset_date_time(&self) -> Option<time::OffsetDateTime> {
+        time::OffsetDateTime::from_unix_timestamp_nanos(self.0 as i128 * 1_000_000).ok()
+    }
+}
+
+const MILLIS_PER_DAY: i64 = 86_400_000;
+
+impl From<SystemTime> for Timestamp {
+    fn from(time: SystemTime) -> Self {
+        match time.duration_since(UNIX_EPOCH) {
+            Ok(after) => Timestamp(after.as_millis() as i64),
+            Err(before) => Timestamp(-(before.duration().as_millis() as i64)),
+        }
+    }
+}
+
+impl From<Timestamp> for SystemTime {
+    fn from(timestamp: Timestamp) -> Self {
+        if timestamp.0 >= 0 {
+            UNIX_EPOCH + Duration::from_millis(timestamp.0 as u64)
+        } else {
+            UNIX_EPOCH - Duration::from_millis(timestamp.0.unsigned_abs())
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> From<chrono::DateTime<Tz>> for Timestamp {
+    fn from(time: chrono::DateTime<Tz>) -> Self {
+        Timestamp(time.timestamp_millis())
+    }
+}
+
+/// Midnight UTC of the date
+#[cfg(feature = "chrono")]
+impl From<chrono::NaiveDate> for Timestamp {
+    fn from(date: chrono::NaiveDate) -> Self {
+        use chrono::Datelike;
+        let days = days_from_civil(date.year() as i64, date.month() as i64, date.day() as i64);
+        Timestamp(days * MILLIS_PER_DAY)
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::OffsetDateTime> for Timestamp {
+    fn from(time: time::OffsetDateTime) -> Self {
+        Timestamp(time.unix_timestamp_nanos().div_euclid(1_000_000) as i64)
+    }
+}
+
+/// Midnight UTC of the date
+#[cfg(feature = "time")]
+impl From<time::Date> for Timestamp {
+    fn from(date: time::Date) -> Self {
+        let days = days_from_civil(date.year() as i64, u8::from(date.month()) as i64, date.day() as i64);
+        Timestamp(days * MILLIS_PER_DAY)
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let days = self.0.div_euclid(MILLIS_PER_DAY);
+        let millis_of_day = self.0.rem_euclid(MILLIS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+            year,
+            month,
+            day,
+            millis_of_day / 3_600_000,
+            millis_of_day / 60_000 % 60,
+            millis_of_day / 1_000 % 60,
+            millis_of_day % 1_000
+        )
+    }
+}
+
+/// Error returned when a string is not an ISO 8601 date or date-time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTimestampError(String);
+
+impl fmt::Display for ParseTimestampError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid timestamp '{}'", self.0)
+    }
+}
+
+impl std::error::Error for ParseTimestampError {}
+
+impl FromStr for Timestamp {
+    type Err = ParseTimestampError;
+
+    /// Parse `YYYY-MM-DD`, `YYYY-MM-DDTHH:MM:SS[.fff]` with an optional `Z` or
+    /// `±HH[:]MM` offset. A missing offset is taken as UTC.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseTimestampError(s.to_string());
+        let bytes = s.as_bytes();
+        let number = |range: std::ops::Range<usize>| -> Result<i64, ParseTimestampError> {
+            let part = s.get(range).ok_or_else(invalid)?;
+            if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(invalid());
+            }
+            part.parse().map_err(|_| invalid())
+        };
+
+        if bytes.len() < 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+            return Err(invalid());
+        }
+        let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+            return Err(invalid());
+        }
+        let mut millis = days_from_civil(year, month, day) * MILLIS_PER_DAY;
+        if bytes.len() == 10 {
+            return Ok(Timestamp(millis));
+        }
+
+        if !(bytes[10] == b'T' || bytes[10] == b' ') || bytes.len() < 19 || bytes[13] != b':' || bytes[16] != b':' {
+            return Err(invalid());
+        }
+        let (hour, minute, second) = (number(11..13)?, number(14..16)?, number(17..19)?);
+        if hour > 23 || minute > 59 || second > 60 {
+            return Err(invalid());
+        }
+        millis += ((hour * 60 + minute) * 60 + second) * 1_000;
+
+        let mut pos = 19;
+        if bytes.get(pos) == Some(&b'.') {
+            let start = pos + 1;
+            pos = start;
+            while pos < bytes.len() && bytes[pos].is_ascii_digit() {
+                pos += 1;
+            }
+            if pos == start {
+                return Err(invalid());
+            }
+            // Only millisecond precision is kept.
+            let fraction = &s[start..pos.min(start + 3)];
+            millis += fraction.parse::<i64>().map_err(|_| invalid())? * 10_i64.pow(3 - fraction.len() as u32);
+        }
+
+        match &s[pos..] {
+            "" | "Z" | "z" => Ok(Timestamp(millis)),
+            offset => {
+                let sign = match offset.as_bytes()[0] {
+                    b'+' => 1,
+                    b'-' => -1,
+                    _ => return Err(invalid()),
+                };
+                let digits: String = offset[1..].chars().filter(|c| *c != ':').collect();
+                if digits.len() != 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(invalid());
+                }
+                let hours: i64 = digits[0..2].parse().map_err(|_| invalid())?;
+                let minutes: i64 = digits[2..4].parse().map_err(|_| invalid())?;
+                Ok(Timestamp(millis - sign * (hours * 60 + minutes) * 60_000))
+            }
+        }
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TimestampVisitor;
+
+        impl<'de> de::Visitor<'de> for TimestampVisitor {
+            type Value = Timestamp;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("an ISO 8601 date string or epoch milliseconds")
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Timestamp, E> {
+                Ok(Timestamp(v))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Timestamp, E> {
+                Ok(Timestamp(v as i64))
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<Timestamp, E> {
+                Ok(Timestamp(v as i64))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Timestamp, E> {
+                v.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(TimestampVisitor)
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Howard Hinnant's days_from_civil / civil_from_days algorithms.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
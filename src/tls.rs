@@ -0,0 +1,200 @@
+use crate::error::Error;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// TLS settings for the connection to HarperDB
+///
+/// The defaults verify the server against the system's trusted roots.
+///
+/// `Debug` output leaves out the certificates and the client identity, so a
+/// logged config never carries a private key or password.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct TlsConfig {
+    /// Extra trusted root certificates, each PEM or DER encoded
+    pub root_certificates: Vec<Vec<u8>>,
+    /// Client certificate presented for mutual TLS
+    pub identity: Option<ClientIdentity>,
+    /// SHA-256 fingerprint of the server's certificate, as hex with or without
+    /// colons (`openssl x509 -noout -fingerprint -sha256`). When set, a server
+    /// is trusted if and only if its certificate matches, which also accepts the
+    /// self-signed certificate of a fresh install. Requires the `rustls-tls`
+    /// feature.
+    pub pinned_certificate: Option<String>,
+    /// Accept any server certificate. For development only: it disables all
+    /// protection against impersonation of the server.
+    pub danger_accept_invalid_certs: bool,
+}
+
+/// `Debug` output redacts the key material and the password
+#[derive(Serialize, Deserialize, Clone)]
+pub enum ClientIdentity {
+    /// PEM with the certificate chain and the private key. Requires the
+    /// `rustls-tls` feature.
+    Pem(Vec<u8>),
+    /// A PKCS#12 archive and its password. Requires the default native TLS
+    /// backend.
+    Pkcs12 { der: Vec<u8>, password: String },
+}
+
+impl fmt::Debug for TlsConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsConfig")
+            .field("root_certificates", &format_args!("[{} certificates]", self.root_certificates.len()))
+            .field("identity", &self.identity)
+            .field("pinned_certificate", &self.pinned_certificate)
+            .field("danger_accept_invalid_certs", &self.danger_accept_invalid_certs)
+            .finish()
+    }
+}
+
+impl fmt::Debug for ClientIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientIdentity::Pem(_) => f.write_str("Pem(<redacted>)"),
+            ClientIdentity::Pkcs12 { .. } => f
+                .debug_struct("Pkcs12")
+                .field("der", &format_args!("<redacted>"))
+                .field("password", &format_args!("<redacted>"))
+                .finish(),
+        }
+    }
+}
+
+pub(crate) fn client_builder(tls: &TlsConfig) -> Result<reqwest::ClientBuilder, Error> {
+    let mut builder = reqwest::Client::builder();
+
+    if let Some(pin) = &tls.pinned_certificate {
+        return pinned_client_builder(builder, pin, tls.identity.as_ref());
+    }
+
+    for certificate in &tls.root_certificates {
+        let certificate = if certificate.starts_with(b"-----BEGIN") {
+            reqwest::Certificate::from_pem(certificate)
+        } else {
+            reqwest::Certificate::from_der(certificate)
+        };
+        builder = builder.add_root_certificate(certificate.map_err(|error| Error::Tls(error.to_string()))?);
+    }
+
+    match &tls.identity {
+        Some(ClientIdentity::Pkcs12 { der, password }) => {
+            let identity = reqwest::Identity::from_pkcs12_der(der, password)
+                .map_err(|error| Error::Tls(error.to_string()))?;
+            builder = builder.use_native_tls().identity(identity);
+        }
+        Some(ClientIdentity::Pem(pem)) => builder = pem_identity(builder, pem)?,
+        None => {}
+    }
+
+    Ok(builder.danger_accept_invalid_certs(tls.danger_accept_invalid_certs))
+}
+
+#[cfg(feature = "rustls-tls")]
+fn pem_identity(builder: reqwest::ClientBuilder, pem: &[u8]) -> Result<reqwest::ClientBuilder, Error> {
+    let identity = reqwest::Identity::from_pem(pem).map_err(|error| Error::Tls(error.to_string()))?;
+    Ok(builder.use_rustls_tls().identity(identity))
+}
+
+#[cfg(not(feature = "rustls-tls"))]
+fn pem_identity(_builder: reqwest::ClientBuilder, _pem: &[u8]) -> Result<reqwest::ClientBuilder, Error> {
+    Err(Error::Tls("PEM client identities require the `rustls-tls` feature".to_string()))
+}
+
+#[cfg(not(feature = "rustls-tls"))]
+fn pinned_client_builder(
+    _builder: reqwest::ClientBuilder,
+    _pin: &str,
+    _identity: Option<&ClientIdentity>,
+) -> Result<reqwest::ClientBuilder, Error> {
+    Err(Error::Tls("certificate pinning requires the `rustls-tls` feature".to_string()))
+}
+
+#[cfg(feature = "rustls-tls")]
+fn pinned_client_builder(
+    builder: reqwest::ClientBuilder,
+    pin: &str,
+    identity: Option<&ClientIdentity>,
+) -> Result<reqwest::ClientBuilder, Error> {
+    use std::sync::Arc;
+
+    let fingerprint = parse_fingerprint(pin)?;
+    let mut config = rustls::ClientConfig::new();
+    config.set_protocols(&["http/1.1".into()]);
+    config
+        .dangerous()
+        .set_certificate_verifier(Arc::new(PinnedCertificate { fingerprint }));
+
+    match identity {
+        Some(ClientIdentity::Pem(pem)) => {
+            let (certificates, key) = parse_pem_identity(pem)?;
+            config
+                .set_single_client_cert(certificates, key)
+                .map_err(|error| Error::Tls(error.to_string()))?;
+        }
+        Some(ClientIdentity::Pkcs12 { .. }) => {
+            return Err(Error::Tls(
+                "PKCS#12 client identities cannot be combined with certificate pinning".to_string(),
+            ))
+        }
+        None => {}
+    }
+
+    Ok(builder.use_preconfigured_tls(config))
+}
+
+#[cfg(feature = "rustls-tls")]
+fn parse_fingerprint(pin: &str) -> Result<Vec<u8>, Error> {
+    let hex: String = pin.chars().filter(|c| *c != ':').collect();
+    let invalid = || Error::Tls(format!("invalid SHA-256 fingerprint '{}'", pin));
+    if hex.len() != 64 || !hex.is_ascii() {
+        return Err(invalid());
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| invalid()))
+        .collect()
+}
+
+#[cfg(feature = "rustls-tls")]
+fn parse_pem_identity(pem: &[u8]) -> Result<(Vec<rustls::Certificate>, rustls::PrivateKey), Error> {
+    use rustls::internal::pemfile;
+
+    let certificates = pemfile::certs(&mut &pem[..]).unwrap_or_default();
+    if certificates.is_empty() {
+        return Err(Error::Tls("no certificate found in PEM client identity".to_string()));
+    }
+    let key = pemfile::pkcs8_private_keys(&mut &pem[..])
+        .unwrap_or_default()
+        .into_iter()
+        .chain(pemfile::rsa_private_keys(&mut &pem[..]).unwrap_or_default())
+        .next()
+        .ok_or_else(|| Error::Tls("no private key found in PEM client identity".to_string()))?;
+    Ok((certificates, key))
+}
+
+/// Trusts exactly the server certificate with the pinned SHA-256 fingerprint.
+#[cfg(feature = "rustls-tls")]
+struct PinnedCertificate {
+    fingerprint: Vec<u8>,
+}
+
+#[cfg(feature = "rustls-tls")]
+impl rustls::ServerCertVerifier for PinnedCertificate {
+    fn verify_server_cert(
+        &self,
+        _roots: &rustls::RootCertStore,
+        presented_certs: &[rustls::Certificate],
+        _dns_name: webpki::DNSNameRef,
+        _ocsp_response: &[u8],
+    ) -> Result<rustls::ServerCertVerified, rustls::TLSError> {
+        let certificate = presented_certs.first().ok_or(rustls::TLSError::NoCertificatesPresented)?;
+        let digest = ring::digest::digest(&ring::digest::SHA256, &certificate.0);
+        if digest.as_ref() == self.fingerprint.as_slice() {
+            Ok(rustls::ServerCertVerified::assertion())
+        } else {
+            Err(rustls::TLSError::General(
+                "server certificate does not match the pinned fingerprint".to_string(),
+            ))
+        }
+    }
+}
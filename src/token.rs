@@ -0,0 +1,88 @@
+use crate::error::Error;
+use crate::timestamp::Timestamp;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::time::Duration;
+
+/// The tokens `Harper::create_authentication_tokens` mints for a user
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuthenticationTokens {
+    /// Authenticates operations, as `Authorization: Bearer <token>`
+    pub operation_token: String,
+    /// Gets a new operation token from `Harper::refresh_operation_token`
+    pub refresh_token: String,
+}
+
+/// The claims of a HarperDB token, read without verifying its signature
+///
+/// Only the server can tell whether a token is valid; the claims tell when to
+/// refresh it without asking.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenClaims {
+    /// `exp`
+    pub expires_at: Option<Timestamp>,
+    /// `iat`
+    pub issued_at: Option<Timestamp>,
+    pub username: Option<String>,
+    /// Every other claim
+    pub other: Map<String, Value>,
+}
+
+impl TokenClaims {
+    /// Decode the payload of a JWT
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let claims = TokenClaims::decode(&tokens.operation_token)?;
+    /// if claims.expires_in().is_some_and(|left| left < Duration::from_secs(60)) {
+    ///     let operation_token = harper_client.refresh_operation_token(&tokens.refresh_token).await?;
+    /// }
+    /// ```
+    pub fn decode(token: &str) -> Result<Self, Error> {
+        let invalid = |message: &str| Error::InvalidToken(message.to_string());
+        let mut parts = token.split('.');
+        let payload = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(_), Some(payload), Some(_), None) => payload,
+            _ => return Err(invalid("expected three dot-separated parts")),
+        };
+        let payload = base64::decode_config(payload.trim_end_matches('='), base64::URL_SAFE_NO_PAD)
+            .map_err(|error| invalid(&error.to_string()))?;
+        let mut other: Map<String, Value> =
+            serde_json::from_slice(&payload).map_err(|error| invalid(&error.to_string()))?;
+
+        // Registered claims are seconds since the Unix epoch.
+        let mut seconds = |claim: &str| {
+            other.remove(claim).and_then(|value| value.as_f64()).map(|seconds| {
+                Timestamp::from_millis((seconds * 1_000.0) as i64)
+            })
+        };
+        let expires_at = seconds("exp");
+        let issued_at = seconds("iat");
+        let username = match other.remove("username") {
+            Some(Value::String(username)) => Some(username),
+            _ => None,
+        };
+
+        Ok(TokenClaims {
+            expires_at,
+            issued_at,
+            username,
+            other,
+        })
+    }
+
+    /// True once the expiry has passed; tokens without one never expire
+    pub fn is_expired(&self) -> bool {
+        match self.expires_at {
+            Some(expires_at) => expires_at <= Timestamp::now(),
+            None => false,
+        }
+    }
+
+    /// Time left until the expiry; zero once expired, `None` without one
+    pub fn expires_in(&self) -> Option<Duration> {
+        let left = self.expires_at?.as_millis() - Timestamp::now().as_millis();
+        Some(Duration::from_millis(left.max(0) as u64))
+    }
+}
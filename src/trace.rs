@@ -0,0 +1,62 @@
+//! Span bookkeeping for the `tracing` feature.
+//!
+//! Only the operation name, schema, table and record count are read from the
+//! operation body. Credentials live in the `Authorization` header and are never
+//! seen here, and record payloads are never recorded.
+
+use crate::error::Error;
+use serde_json::Value;
+use std::time::Duration;
+use tracing::field::Empty;
+use tracing::Span;
+
+pub(crate) fn operation_span(operation: &Value) -> Span {
+    let field = |name: &str| operation.get(name).and_then(Value::as_str).unwrap_or_default().to_string();
+
+    let span = tracing::info_span!(
+        "harperdb",
+        operation = %field("operation"),
+        schema = Empty,
+        table = Empty,
+        record_count = Empty,
+        node = Empty,
+        status = Empty,
+        latency_ms = Empty,
+    );
+    if let Some(schema) = operation.get("schema").and_then(Value::as_str) {
+        span.record("schema", schema);
+    }
+    if let Some(table) = operation.get("table").and_then(Value::as_str) {
+        span.record("table", table);
+    }
+    let record_count = operation
+        .get("records")
+        .or_else(|| operation.get("hash_values"))
+        .and_then(Value::as_array)
+        .map(Vec::len);
+    if let Some(record_count) = record_count {
+        span.record("record_count", record_count as u64);
+    }
+    span
+}
+
+pub(crate) fn record_node(node: &str) {
+    Span::current().record("node", node);
+}
+
+pub(crate) fn record_result(span: &Span, result: &Result<reqwest::Response, Error>, latency: Duration) {
+    span.record("latency_ms", latency.as_millis() as u64);
+    match result {
+        Ok(res) => {
+            span.record("status", res.status().as_u16());
+        }
+        Err(error) => {
+            if let Error::Http(error) | Error::Timeout(error) = error {
+                if let Some(status) = error.status() {
+                    span.record("status", status.as_u16());
+                }
+            }
+            span.in_scope(|| tracing::warn!(error = %error, "operation failed"));
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use crate::error::Error;
+use crate::timestamp::Timestamp;
+use crate::Harper;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+/// A HarperDB user, from `Harper::list_users` or `Harper::user_info`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct User {
+    pub username: String,
+    pub active: bool,
+    /// `None` when the user's role no longer exists
+    pub role: Option<Role>,
+    #[serde(rename = "__createdtime__", default, skip_serializing_if = "Option::is_none")]
+    pub created_time: Option<Timestamp>,
+    #[serde(rename = "__updatedtime__", default, skip_serializing_if = "Option::is_none")]
+    pub updated_time: Option<Timestamp>,
+    /// Every other attribute the server reports
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// A role, from `Harper::list_roles` or expanded into a `User`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Role {
+    pub id: String,
+    /// The role name
+    pub role: String,
+    /// `super_user`, `cluster_user` and the schema permissions
+    #[serde(default)]
+    pub permission: Value,
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+impl Role {
+    pub fn is_super_user(&self) -> bool {
+        self.permission["super_user"] == true
+    }
+}
+
+/// A user as `Harper::provision_users` should leave it
+///
+/// Read from a JSON array, or from the `[[users]]` tables of a TOML file:
+///
+/// ```toml
+/// [[users]]
+/// username = "reporting"
+/// role = "read_only"
+/// password = "secret"
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UserSpec {
+    pub username: String,
+    /// Role name or id
+    pub role: String,
+    /// Only used to create a missing user; existing passwords are left alone
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default = "active_by_default")]
+    pub active: bool,
+}
+
+fn active_by_default() -> bool {
+    true
+}
+
+impl UserSpec {
+    /// Read a list of users from a `.toml` file, or from a JSON file holding an
+    /// array
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<UserSpec>, Error> {
+        #[derive(Deserialize)]
+        struct UsersFile {
+            users: Vec<UserSpec>,
+        }
+
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        if path.extension().is_some_and(|extension| extension == "toml") {
+            let file: UsersFile = toml::from_str(&contents)
+                .map_err(|error| Error::Provision(format!("invalid users file {}: {}", path.display(), error)))?;
+            Ok(file.users)
+        } else {
+            Ok(serde_json::from_str(&contents)?)
+        }
+    }
+}
+
+/// One change `Harper::provision_users` makes, or would make on a dry run
+#[derive(Debug, Clone, PartialEq)]
+pub enum UserChange {
+    Create { username: String, role: String },
+    /// Only the fields that change are set
+    Update {
+        username: String,
+        role: Option<String>,
+        active: Option<bool>,
+    },
+    Deactivate { username: String },
+}
+
+impl fmt::Display for UserChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UserChange::Create { username, role } => write!(f, "create {} with role {}", username, role),
+            UserChange::Update { username, role, active } => {
+                write!(f, "update {}:", username)?;
+                if let Some(role) = role {
+                    write!(f, " role {}", role)?;
+                }
+                match active {
+                    Some(true) => write!(f, " activate"),
+                    Some(false) => write!(f, " deactivate"),
+                    None => Ok(()),
+                }
+            }
+            UserChange::Deactivate { username } => write!(f, "deactivate {}", username),
+        }
+    }
+}
+
+/// What `Harper::provision_users` changed, or would change on a dry run
+#[derive(Debug, Clone)]
+pub struct ProvisionReport {
+    /// Creations first, then updates, then deactivations, with role names
+    pub changes: Vec<UserChange>,
+    /// False on a dry run
+    pub applied: bool,
+}
+
+/// A failed `Harper::provision_users` and how far it got
+#[derive(Debug)]
+pub struct ProvisionError {
+    /// The changes applied before the failure, in order; empty when it failed
+    /// before changing anything
+    pub applied: Vec<UserChange>,
+    /// The change that failed to apply
+    pub failed: Option<UserChange>,
+    pub error: Error,
+}
+
+impl fmt::Display for ProvisionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.failed {
+            Some(change) => write!(
+                f,
+                "{} failed after {} applied changes: {}",
+                change,
+                self.applied.len(),
+                self.error
+            ),
+            None => write!(f, "{}", self.error),
+        }
+    }
+}
+
+impl std::error::Error for ProvisionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<Error> for ProvisionError {
+    fn from(error: Error) -> Self {
+        ProvisionError {
+            applied: Vec::new(),
+            failed: None,
+            error,
+        }
+    }
+}
+
+/// Replace role ids by the role objects `list_roles` reports, for servers that
+/// answer with ids only
+pub(crate) async fn expand_roles(harper: &Harper, mut users: Vec<Value>) -> Result<Vec<User>, Error> {
+    if users.iter().any(|user| user["role"].is_string()) {
+        let roles: HashMap<String, Value> = list_roles(harper)
+            .await?
+            .into_iter()
+            .filter_map(|role| Some((role["id"].as_str()?.to_string(), role)))
+            .collect();
+        for user in &mut users {
+            if let Some(id) = user["role"].as_str() {
+                user["role"] = roles.get(id).cloned().unwrap_or(Value::Null);
+            }
+        }
+    }
+    users.into_iter().map(|user| Ok(serde_json::from_value(user)?)).collect()
+}
+
+async fn list_roles(harper: &Harper) -> Result<Vec<Value>, Error> {
+    let res = Error::check(harper.request(&json!({ "operation": "list_roles" })).await?).await?;
+    Ok(res.json().await?)
+}
+
+pub(crate) async fn provision(
+    harper: &Harper,
+    desired: &[UserSpec],
+    dry_run: bool,
+) -> Result<ProvisionReport, ProvisionError> {
+    for (index, spec) in desired.iter().enumerate() {
+        if desired[..index].iter().any(|other| other.username == spec.username) {
+            return Err(Error::Provision(format!("user {} is listed more than once", spec.username)).into());
+        }
+    }
+
+    let roles: Vec<Role> = serde_json::from_value(Value::Array(list_roles(harper).await?)).map_err(Error::from)?;
+    let existing = harper.list_users().await?;
+
+    // Each change with the operation applying it
+    let mut creates = Vec::new();
+    let mut updates = Vec::new();
+    let mut deactivations = Vec::new();
+
+    for spec in desired {
+        let role = roles
+            .iter()
+            .find(|role| role.id == spec.role)
+            .or_else(|| roles.iter().find(|role| role.role == spec.role))
+            .ok_or_else(|| Error::Provision(format!("user {} has an unknown role {}", spec.username, spec.role)))?;
+
+        match existing.iter().find(|user| user.username == spec.username) {
+            None => {
+                let password = spec.password.as_ref().ok_or_else(|| {
+                    Error::Provision(format!("user {} does not exist and has no password", spec.username))
+                })?;
+                let change = UserChange::Create {
+                    username: spec.username.clone(),
+                    role: role.role.clone(),
+                };
+                let operation = json!({
+                    "operation": "add_user",
+                    "username": &spec.username,
+                    "password": password,
+                    "role": &role.id,
+                    "active": spec.active,
+                });
+                creates.push((change, operation));
+            }
+            Some(user) => {
+                let role_changed = user.role.as_ref().is_none_or(|current| current.id != role.id);
+                let active_changed = user.active != spec.active;
+                if !role_changed && !active_changed {
+                    continue;
+                }
+                let mut operation = json!({ "operation": "alter_user", "username": &spec.username });
+                if role_changed {
+                    operation["role"] = json!(&role.id);
+                }
+                if active_changed {
+                    operation["active"] = json!(spec.active);
+                }
+                let change = UserChange::Update {
+                    username: spec.username.clone(),
+                    role: if role_changed { Some(role.role.clone()) } else { None },
+                    active: if active_changed { Some(spec.active) } else { None },
+                };
+                updates.push((change, operation));
+            }
+        }
+    }
+
+    // Never lock the client out by deactivating the user it signs in as.
+    let own_username = harper.config.username.as_str();
+    for user in &existing {
+        let listed = desired.iter().any(|spec| spec.username == user.username);
+        if !listed && user.active && user.username != own_username {
+            let change = UserChange::Deactivate {
+                username: user.username.clone(),
+            };
+            let operation = json!({ "operation": "alter_user", "username": &user.username, "active": false });
+            deactivations.push((change, operation));
+        }
+    }
+
+    let (changes, operations): (Vec<UserChange>, Vec<Value>) =
+        creates.into_iter().chain(updates).chain(deactivations).unzip();
+    if !dry_run {
+        for (index, operation) in operations.iter().enumerate() {
+            let res = match harper.request(operation).await {
+                Ok(res) => Error::check(res).await,
+                Err(error) => Err(error),
+            };
+            if let Err(error) = res {
+                let mut applied = changes;
+                let failed = applied.drain(index..).next();
+                return Err(ProvisionError { applied, failed, error });
+            }
+        }
+    }
+
+    Ok(ProvisionReport {
+        changes,
+        applied: !dry_run,
+    })
+}
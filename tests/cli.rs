@@ -0,0 +1,49 @@
+#![cfg(feature = "cli")]
+
+use std::process::Command;
+
+fn harperdb() -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_harperdb"));
+    command
+        .env_remove("HARPERDB_PROFILE")
+        .env("HARPERDB_URL", "http://0.0.0.0:9925/")
+        .env("HARPERDB_USERNAME", "HDB_ADMIN")
+        .env("HARPERDB_PASSWORD", "password")
+        .env_remove("HARPERDB_SCHEMA");
+    command
+}
+
+#[test]
+fn cli_sql_csv() {
+    let output = harperdb()
+        .args(["--format", "csv", "sql", "SELECT id FROM testing.crud_table_test WHERE id = 'none'"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn cli_table_without_schema() {
+    let output = harperdb().args(["table", "describe", "dog"]).output().unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("no default schema configured"), "{}", stderr);
+}
+
+#[test]
+fn cli_shell_meta_commands() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut shell = harperdb()
+        .env("HOME", std::env::temp_dir())
+        .arg("shell")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    shell.stdin.take().unwrap().write_all(b"\\timing\n\\q\n").unwrap();
+    let output = shell.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Timing is on."));
+}